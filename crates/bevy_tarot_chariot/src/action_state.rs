@@ -0,0 +1,400 @@
+//! Per frame state of mapped actions
+//!
+//! Presses of mappings with a buffer window stay available through `ActionState::consume_buffered`
+//! until they are consumed or the window runs out.
+//!
+//! # Example usage:
+//! ```
+//! use std::time::Duration;
+//! use serde::Serialize;
+//! use bevy_app::prelude::*;
+//! use bevy_time::Time;
+//! use bevy_tarot_chariot::{ActionState, ButtonMapping, InputAction, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::{prelude::*, InputPlugin};
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(
+//!             MappedButtons::new_single(SimpleInputAction::Jump, KeyCode::Space.into())
+//!                 .with_buffer(Duration::from_millis(100)),
+//!         );
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mut app = App::new();
+//!     app.add_plugins(InputPlugin);
+//!     app.init_resource::<Time>();
+//!     app.add_plugins(bevy_tarot_chariot::plugin::<SimpleInputAction>);
+//!     app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+//!     app.update();
+//!     let state = app.world().resource::<ActionState<SimpleInputAction>>();
+//!     assert!(state.just_pressed(&SimpleInputAction::Jump));
+//!     assert_eq!(state.value(&SimpleInputAction::Jump), 1.);
+//!     app.update();
+//!     let mut state = app.world_mut().resource_mut::<ActionState<SimpleInputAction>>();
+//!     assert!(state.consume_buffered(&SimpleInputAction::Jump));
+//!     assert!(!state.consume_buffered(&SimpleInputAction::Jump));
+//! }
+//! ```
+
+use crate::{
+    accumulate_mouse_axis_input, axis_plugin, capture_rebind, record_action_state,
+    replay_action_state, replay_inactive, replay_time, ButtonMapping, InputAction, InputContexts,
+    InputRecorder, InputReplay, InteractionState, MouseAxisInput, RebindSession, SequenceProgress,
+    TouchButton,
+};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_input::prelude::*;
+use bevy_input::InputSystem;
+use bevy_math::Vec2;
+use bevy_time::{Time, TimeSystem};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Evaluates `ButtonMapping<A>` once per frame into `ActionState<A>`.
+/// Inserts `A::default_mapping()` if no `ButtonMapping<A>` exists yet.
+pub fn plugin<A: InputAction>(app: &mut App) {
+    if !app.world().contains_resource::<MouseAxisInput>() {
+        axis_plugin(app);
+    }
+    if !app.world().contains_resource::<ButtonMapping<A>>() {
+        app.insert_resource(A::default_mapping());
+    }
+    app.init_resource::<ActionState<A>>();
+    app.init_resource::<InputContexts<A>>();
+    app.init_resource::<RebindSession<A>>();
+    app.init_resource::<InputRecorder<A>>();
+    app.init_resource::<InputReplay<A>>();
+    app.add_systems(
+        PreUpdate,
+        (
+            update_action_state::<A>.run_if(replay_inactive::<A>),
+            replay_action_state::<A>,
+        )
+            .chain()
+            .in_set(ActionStateSystem)
+            .after(InputSystem)
+            .after(accumulate_mouse_axis_input),
+    );
+    app.add_systems(PreUpdate, record_action_state::<A>.after(ActionStateSystem));
+    app.add_systems(First, replay_time::<A>.before(TimeSystem));
+    app.add_systems(PreUpdate, capture_rebind::<A>.after(InputSystem));
+}
+
+/// Systems that write `ActionState<A>`. Systems reading it in `PreUpdate` should run after this set.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionStateSystem;
+
+/// All input resources that `ButtonMapping` can read. Missing resources are treated as unpressed.
+#[derive(SystemParam)]
+pub struct InputDevices<'w> {
+    /// Keyboard input
+    pub key_codes: Option<Res<'w, ButtonInput<KeyCode>>>,
+    /// Mouse button input
+    pub mouse_buttons: Option<Res<'w, ButtonInput<MouseButton>>>,
+    /// Gamepad button input
+    pub gamepad_buttons: Option<Res<'w, ButtonInput<GamepadButton>>>,
+    /// Gamepad axis input
+    pub gamepad_axes: Option<Res<'w, Axis<GamepadAxis>>>,
+    /// Analog gamepad button input
+    pub gamepad_button_axes: Option<Res<'w, Axis<GamepadButton>>>,
+    /// Mouse wheel and motion
+    pub mouse_axes: Option<Res<'w, MouseAxisInput>>,
+    /// Virtual on-screen buttons and gestures
+    pub touch: Option<Res<'w, ButtonInput<TouchButton>>>,
+}
+
+impl InputDevices<'_> {
+    /// Borrow all available input resources.
+    pub fn get(&self) -> DeviceInput<'_> {
+        DeviceInput {
+            key_codes: self.key_codes.as_deref(),
+            mouse_buttons: self.mouse_buttons.as_deref(),
+            gamepad_buttons: self.gamepad_buttons.as_deref(),
+            gamepad_axes: self.gamepad_axes.as_deref(),
+            gamepad_button_axes: self.gamepad_button_axes.as_deref(),
+            mouse_axes: self.mouse_axes.as_deref(),
+            touch: self.touch.as_deref(),
+        }
+    }
+}
+
+/// Borrowed input resources that an `ActionState` is updated from. `None` is treated as unpressed.
+#[derive(Default, Copy, Clone)]
+pub struct DeviceInput<'a> {
+    /// Keyboard input
+    pub key_codes: Option<&'a ButtonInput<KeyCode>>,
+    /// Mouse button input
+    pub mouse_buttons: Option<&'a ButtonInput<MouseButton>>,
+    /// Gamepad button input
+    pub gamepad_buttons: Option<&'a ButtonInput<GamepadButton>>,
+    /// Gamepad axis input
+    pub gamepad_axes: Option<&'a Axis<GamepadAxis>>,
+    /// Analog gamepad button input
+    pub gamepad_button_axes: Option<&'a Axis<GamepadButton>>,
+    /// Mouse wheel and motion
+    pub mouse_axes: Option<&'a MouseAxisInput>,
+    /// Virtual on-screen buttons and gestures
+    pub touch: Option<&'a ButtonInput<TouchButton>>,
+}
+
+/// State of a single action in the current frame.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionData {
+    /// Any mapped button is pressed or any mapped axis is not 0.
+    pub pressed: bool,
+    /// `pressed` changed to true this frame.
+    pub just_pressed: bool,
+    /// `pressed` changed to false this frame.
+    pub just_released: bool,
+    /// How long the action has been pressed. Reset on release.
+    pub held_duration: Duration,
+    /// Analog value of the action. Buttons count as 1.
+    pub value: f32,
+    /// Dual axis value of the action.
+    pub axis_pair: Vec2,
+    /// Hold, tap, double tap and charge of the action.
+    pub interaction: InteractionState,
+}
+
+impl ActionData {
+    /// Advance to the next frame with the newly read values.
+    pub fn update(&mut self, pressed: bool, value: f32, axis_pair: Vec2, delta: Duration) {
+        self.just_pressed = pressed && !self.pressed;
+        self.just_released = !pressed && self.pressed;
+        self.held_duration = if pressed && !self.just_pressed {
+            self.held_duration + delta
+        } else {
+            Duration::ZERO
+        };
+        self.pressed = pressed;
+        self.value = value;
+        self.axis_pair = axis_pair;
+    }
+}
+
+/// Evaluated state of all actions of type `A`, updated once per frame in `PreUpdate`.
+#[derive(Resource, Clone, Debug)]
+pub struct ActionState<A: InputAction> {
+    /// State per action.
+    pub(crate) data: HashMap<A, ActionData>,
+    /// Progress of the `ButtonSequence`s of each action.
+    pub(crate) sequences: HashMap<A, SmallVec<[SequenceProgress; 1]>>,
+    /// Time since the last unconsumed press of buffered actions.
+    pub(crate) buffered: HashMap<A, Duration>,
+}
+
+impl<A: InputAction> Default for ActionState<A> {
+    fn default() -> Self {
+        Self {
+            data: HashMap::new(),
+            sequences: HashMap::new(),
+            buffered: HashMap::new(),
+        }
+    }
+}
+
+impl<A: InputAction> ActionState<A> {
+    /// Full state of `action` if it has been evaluated.
+    pub fn get(&self, action: &A) -> Option<&ActionData> {
+        self.data.get(action)
+    }
+
+    /// Mutable state of `action`, inserting a default state if it has none yet.
+    pub fn get_mut(&mut self, action: A) -> &mut ActionData {
+        self.data.entry(action).or_default()
+    }
+
+    /// Check if the `action` is currently pressed.
+    pub fn pressed(&self, action: &A) -> bool {
+        self.get(action).map(|d| d.pressed).unwrap_or_default()
+    }
+
+    /// Check if the `action` was pressed in this cycle.
+    pub fn just_pressed(&self, action: &A) -> bool {
+        self.get(action).map(|d| d.just_pressed).unwrap_or_default()
+    }
+
+    /// Check if the `action` was released in this cycle.
+    pub fn just_released(&self, action: &A) -> bool {
+        self.get(action).map(|d| d.just_released).unwrap_or_default()
+    }
+
+    /// How long the `action` has been held.
+    pub fn held_duration(&self, action: &A) -> Duration {
+        self.get(action).map(|d| d.held_duration).unwrap_or_default()
+    }
+
+    /// Analog value of the `action`.
+    pub fn value(&self, action: &A) -> f32 {
+        self.get(action).map(|d| d.value).unwrap_or_default()
+    }
+
+    /// Dual axis value of the `action`.
+    pub fn axis_pair(&self, action: &A) -> Vec2 {
+        self.get(action).map(|d| d.axis_pair).unwrap_or_default()
+    }
+
+    /// Check if the `action` has been pressed for at least its hold time.
+    pub fn held(&self, action: &A) -> bool {
+        self.interaction(action).held
+    }
+
+    /// Check if the `action` reached its hold time in this cycle.
+    pub fn just_held(&self, action: &A) -> bool {
+        self.interaction(action).just_held
+    }
+
+    /// Check if the `action` was tapped in this cycle.
+    pub fn tapped(&self, action: &A) -> bool {
+        self.interaction(action).tapped
+    }
+
+    /// Check if the `action` was double tapped in this cycle.
+    pub fn double_tapped(&self, action: &A) -> bool {
+        self.interaction(action).double_tapped
+    }
+
+    /// Charge level of the `action` from 0 to 1.
+    pub fn charge(&self, action: &A) -> f32 {
+        self.interaction(action).charge
+    }
+
+    /// Charge level of the `action` if it was released in this cycle, 0 otherwise.
+    pub fn released_charge(&self, action: &A) -> f32 {
+        self.interaction(action).released_charge
+    }
+
+    /// Hold, tap, double tap and charge of the `action`.
+    pub fn interaction(&self, action: &A) -> InteractionState {
+        self.get(action).map(|d| d.interaction).unwrap_or_default()
+    }
+
+    /// Check if the `action` has a buffered press.
+    pub fn is_buffered(&self, action: &A) -> bool {
+        self.buffered.contains_key(action)
+    }
+
+    /// Returns true if the `action` has a buffered press and clears it.
+    pub fn consume_buffered(&mut self, action: &A) -> bool {
+        self.buffered.remove(action).is_some()
+    }
+
+    /// Clear the buffered press of `action` without using it.
+    pub fn clear_buffered(&mut self, action: &A) {
+        self.buffered.remove(action);
+    }
+
+    /// Iterate over all evaluated actions.
+    pub fn iter(&self) -> impl Iterator<Item = (&A, &ActionData)> {
+        self.data.iter()
+    }
+
+    /// Read all mapped buttons and axes of `mapping` for the next frame.
+    pub fn update(
+        &mut self,
+        mapping: &ButtonMapping<A>,
+        contexts: &InputContexts<A>,
+        input: DeviceInput<'_>,
+        delta: Duration,
+    ) {
+        let actions = mapping.actions().collect::<HashSet<A>>();
+        for action in actions.iter() {
+            let button_pressed = mapping.pressed_in(
+                action,
+                contexts,
+                input.key_codes,
+                input.mouse_buttons,
+                input.gamepad_buttons,
+            ) || mapping.touch_pressed_in(action, contexts, input.touch);
+            let axis_value = mapping.axis_value(
+                action,
+                input.gamepad_axes,
+                input.gamepad_button_axes,
+                input.mouse_axes,
+            );
+            let axis_pair = mapping.dual_axis_value(
+                action,
+                input.gamepad_axes,
+                input.gamepad_button_axes,
+                input.mouse_axes,
+            );
+            let sequence_completed = mapping
+                .get_from_action(action)
+                .filter(|m| m.get_context().is_none_or(|c| contexts.is_active(c)))
+                .map(|m| {
+                    let progress = self.sequences.entry(*action).or_default();
+                    progress.resize(m.get_sequences().len(), SequenceProgress::default());
+                    m.get_sequences()
+                        .iter()
+                        .zip(progress.iter_mut())
+                        .fold(false, |completed, (sequence, progress)| {
+                            sequence.advance(
+                                progress,
+                                delta,
+                                input.key_codes,
+                                input.mouse_buttons,
+                                input.gamepad_buttons,
+                            ) || completed
+                        })
+                })
+                .unwrap_or_default();
+            let button_pressed = button_pressed || sequence_completed;
+            let value = if axis_value == 0. && button_pressed {
+                1.
+            } else {
+                axis_value
+            };
+            let pressed = button_pressed || value != 0. || axis_pair != Vec2::ZERO;
+            let (interactions, buffer) = mapping
+                .get_from_action(action)
+                .map(|m| (*m.get_interactions(), m.get_buffer()))
+                .unwrap_or_default();
+            let data = self.get_mut(*action);
+            data.update(pressed, value, axis_pair, delta);
+            data.interaction.update(pressed, delta, &interactions);
+            let just_pressed = data.just_pressed;
+            match buffer {
+                Some(_) if just_pressed => {
+                    self.buffered.insert(*action, Duration::ZERO);
+                }
+                Some(window) => {
+                    if let Some(age) = self.buffered.get_mut(action) {
+                        *age += delta;
+                        if *age > window {
+                            self.buffered.remove(action);
+                        }
+                    }
+                }
+                None => {
+                    self.buffered.remove(action);
+                }
+            }
+        }
+        self.data.retain(|a, _| actions.contains(a));
+        self.sequences.retain(|a, _| actions.contains(a));
+        self.buffered.retain(|a, _| actions.contains(a));
+    }
+}
+
+/// Reads all mapped buttons and axes of `A` into `ActionState<A>`.
+pub fn update_action_state<A: InputAction>(
+    mapping: Res<ButtonMapping<A>>,
+    contexts: Res<InputContexts<A>>,
+    devices: InputDevices,
+    time: Res<Time>,
+    mut state: ResMut<ActionState<A>>,
+) {
+    state.update(&mapping, &contexts, devices.get(), time.delta());
+}
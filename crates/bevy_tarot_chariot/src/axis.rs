@@ -0,0 +1,450 @@
+//! Analog input (sticks, triggers, mouse wheel and mouse motion)
+//!
+//! # Example usage:
+//! ```
+//! use bevy_tarot_chariot::AxisBinding;
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! let stick_x = GamepadAxis::new(Gamepad::new(0), GamepadAxisType::LeftStickX);
+//! let binding = AxisBinding::from(stick_x).with_dead_zone(0.2).inverted();
+//! let mut axes = Axis::<GamepadAxis>::default();
+//! axes.set(stick_x, 0.1);
+//! assert_eq!(binding.value(Some(&axes), None, None), 0.);
+//! axes.set(stick_x, 1.);
+//! assert_eq!(binding.value(Some(&axes), None, None), -1.);
+//! ```
+
+use crate::{InputAction, BUTTON_COUNT};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_input::mouse::{MouseMotion, MouseWheel};
+use bevy_input::prelude::*;
+use bevy_input::InputSystem;
+use bevy_math::Vec2;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+
+/// Registers `MouseAxisInput` and the system that accumulates mouse events into it.
+pub fn axis_plugin(app: &mut App) {
+    app.init_resource::<MouseAxisInput>();
+    app.add_systems(PreUpdate, accumulate_mouse_axis_input.after(InputSystem));
+}
+
+/// Mouse wheel and mouse motion accumulated over the current frame.
+#[derive(Resource, Default, Copy, Clone, Debug)]
+pub struct MouseAxisInput {
+    /// Sum of all `MouseWheel` events this frame.
+    pub wheel: Vec2,
+    /// Sum of all `MouseMotion` events this frame.
+    pub motion: Vec2,
+}
+
+/// Sums up mouse wheel and motion events of this frame into `MouseAxisInput`.
+pub fn accumulate_mouse_axis_input(
+    mut mouse_axes: ResMut<MouseAxisInput>,
+    mut wheel: EventReader<MouseWheel>,
+    mut motion: EventReader<MouseMotion>,
+) {
+    mouse_axes.wheel = wheel.read().map(|e| Vec2::new(e.x, e.y)).sum();
+    mouse_axes.motion = motion.read().map(|e| e.delta).sum();
+}
+
+/// Direction of a mouse axis.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MouseAxis {
+    /// Horizontal
+    X,
+    /// Vertical
+    Y,
+}
+
+/// Generic abstraction over gamepad axes, analog gamepad buttons and mouse axes.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GenericAxis {
+    /// Gamepad stick (or Z) axis
+    Gamepad(GamepadAxis),
+    /// Analog value of a gamepad button (e.g. triggers)
+    GamepadButton(GamepadButton),
+    /// Gamepad axis on any gamepad, or on the player's gamepad (see `PlayerSlots`)
+    AnyGamepad(GamepadAxisType),
+    /// Analog gamepad button on any gamepad, or on the player's gamepad
+    AnyGamepadButton(GamepadButtonType),
+    /// Mouse wheel input
+    MouseWheel(MouseAxis),
+    /// Mouse motion input
+    MouseMotion(MouseAxis),
+}
+
+impl From<GamepadAxis> for GenericAxis {
+    fn from(value: GamepadAxis) -> Self {
+        Self::Gamepad(value)
+    }
+}
+
+impl From<GamepadAxisType> for GenericAxis {
+    fn from(value: GamepadAxisType) -> Self {
+        Self::AnyGamepad(value)
+    }
+}
+
+impl GenericAxis {
+    /// Raw value of the axis.
+    /// If an optional input is omitted it returns 0. for that axis.
+    pub fn value(
+        &self,
+        gamepad_axes: Option<&Axis<GamepadAxis>>,
+        gamepad_buttons: Option<&Axis<GamepadButton>>,
+        mouse_axes: Option<&MouseAxisInput>,
+    ) -> f32 {
+        match self {
+            GenericAxis::Gamepad(a) => gamepad_axes.and_then(|ax| ax.get(*a)).unwrap_or_default(),
+            GenericAxis::GamepadButton(b) => gamepad_buttons
+                .and_then(|ax| ax.get(*b))
+                .unwrap_or_default(),
+            GenericAxis::AnyGamepad(t) => gamepad_axes
+                .map(|ax| strongest(ax, |a| a.axis_type == *t))
+                .unwrap_or_default(),
+            GenericAxis::AnyGamepadButton(t) => gamepad_buttons
+                .map(|ax| strongest(ax, |b| b.button_type == *t))
+                .unwrap_or_default(),
+            GenericAxis::MouseWheel(m) => mouse_axes
+                .map(|ax| m.select(ax.wheel))
+                .unwrap_or_default(),
+            GenericAxis::MouseMotion(m) => mouse_axes
+                .map(|ax| m.select(ax.motion))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Gamepad values are normalized to [-1, 1], mouse values are not.
+    pub fn is_bounded(&self) -> bool {
+        !matches!(self, GenericAxis::MouseWheel(_) | GenericAxis::MouseMotion(_))
+    }
+
+    /// Resolve a device independent gamepad axis to the axis of `gamepad`.
+    pub fn for_gamepad(self, gamepad: Gamepad) -> Self {
+        match self {
+            GenericAxis::AnyGamepad(t) => GamepadAxis::new(gamepad, t).into(),
+            GenericAxis::AnyGamepadButton(t) => {
+                GenericAxis::GamepadButton(GamepadButton::new(gamepad, t))
+            }
+            _ => self,
+        }
+    }
+
+    /// Gamepad of an axis bound to a specific gamepad.
+    pub fn gamepad(&self) -> Option<Gamepad> {
+        match self {
+            GenericAxis::Gamepad(a) => Some(a.gamepad),
+            GenericAxis::GamepadButton(b) => Some(b.gamepad),
+            _ => None,
+        }
+    }
+}
+
+/// Value with the largest magnitude of all devices matching `filter`.
+fn strongest<T: Copy + Eq + std::hash::Hash + Send + Sync>(
+    axis: &Axis<T>,
+    filter: impl Fn(&T) -> bool,
+) -> f32 {
+    axis.devices()
+        .filter(|d| filter(d))
+        .filter_map(|d| axis.get(*d))
+        .fold(0., |acc: f32, v| if v.abs() > acc.abs() { v } else { acc })
+}
+
+impl MouseAxis {
+    fn select(&self, value: Vec2) -> f32 {
+        match self {
+            MouseAxis::X => value.x,
+            MouseAxis::Y => value.y,
+        }
+    }
+}
+
+/// A single axis with dead zone, sensitivity and inversion.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AxisBinding {
+    /// Axis this binding reads.
+    pub axis: GenericAxis,
+    /// Absolute values below this are treated as 0.
+    #[serde(default)]
+    pub dead_zone: f32,
+    /// Factor applied to the value after the dead zone.
+    #[serde(default = "default_sensitivity")]
+    pub sensitivity: f32,
+    /// Flip the sign of the value.
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+fn default_sensitivity() -> f32 {
+    1.
+}
+
+impl From<GenericAxis> for AxisBinding {
+    fn from(value: GenericAxis) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<GamepadAxis> for AxisBinding {
+    fn from(value: GamepadAxis) -> Self {
+        Self::new(value.into())
+    }
+}
+
+impl AxisBinding {
+    /// Binding without dead zone, with sensitivity 1. and not inverted.
+    pub fn new(axis: GenericAxis) -> Self {
+        Self {
+            axis,
+            dead_zone: 0.,
+            sensitivity: default_sensitivity(),
+            inverted: false,
+        }
+    }
+
+    /// Set the dead zone.
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+
+    /// Set the sensitivity.
+    pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.sensitivity = sensitivity;
+        self
+    }
+
+    /// Invert the axis.
+    pub fn inverted(mut self) -> Self {
+        self.inverted = !self.inverted;
+        self
+    }
+
+    /// Value after applying dead zone, sensitivity and inversion.
+    /// If an optional input is omitted it returns 0. for that axis.
+    pub fn value(
+        &self,
+        gamepad_axes: Option<&Axis<GamepadAxis>>,
+        gamepad_buttons: Option<&Axis<GamepadButton>>,
+        mouse_axes: Option<&MouseAxisInput>,
+    ) -> f32 {
+        self.apply(self.axis.value(gamepad_axes, gamepad_buttons, mouse_axes))
+    }
+
+    /// Resolve a device independent gamepad axis to the axis of `gamepad`.
+    pub fn for_gamepad(mut self, gamepad: Gamepad) -> Self {
+        self.axis = self.axis.for_gamepad(gamepad);
+        self
+    }
+
+    /// Apply dead zone, sensitivity and inversion to a raw value.
+    /// Bounded axes are rescaled so that the output still starts at 0. at the edge of the dead zone.
+    pub fn apply(&self, raw: f32) -> f32 {
+        let magnitude = raw.abs();
+        if magnitude <= self.dead_zone {
+            return 0.;
+        }
+        let magnitude = if self.axis.is_bounded() && self.dead_zone < 1. {
+            ((magnitude - self.dead_zone) / (1. - self.dead_zone)).min(1.)
+        } else {
+            magnitude
+        };
+        let value = magnitude.copysign(raw) * self.sensitivity;
+        if self.inverted {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+/// Two axes that are read together, e.g. a gamepad stick.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DualAxisBinding {
+    /// Horizontal axis
+    pub x: AxisBinding,
+    /// Vertical axis
+    pub y: AxisBinding,
+    /// Radial dead zone, applied to the length of the raw vector before the per axis settings.
+    #[serde(default)]
+    pub dead_zone: f32,
+}
+
+impl DualAxisBinding {
+    /// Simpel Constructor
+    pub fn new(x: AxisBinding, y: AxisBinding) -> Self {
+        Self {
+            x,
+            y,
+            dead_zone: 0.,
+        }
+    }
+
+    /// Left stick of whichever gamepad is used (see `GenericAxis::AnyGamepad`).
+    pub fn any_left_stick() -> Self {
+        Self::new(
+            GenericAxis::from(GamepadAxisType::LeftStickX).into(),
+            GenericAxis::from(GamepadAxisType::LeftStickY).into(),
+        )
+    }
+
+    /// Right stick of whichever gamepad is used (see `GenericAxis::AnyGamepad`).
+    pub fn any_right_stick() -> Self {
+        Self::new(
+            GenericAxis::from(GamepadAxisType::RightStickX).into(),
+            GenericAxis::from(GamepadAxisType::RightStickY).into(),
+        )
+    }
+
+    /// Left stick of `gamepad`.
+    pub fn left_stick(gamepad: Gamepad) -> Self {
+        Self::new(
+            GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX).into(),
+            GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY).into(),
+        )
+    }
+
+    /// Right stick of `gamepad`.
+    pub fn right_stick(gamepad: Gamepad) -> Self {
+        Self::new(
+            GamepadAxis::new(gamepad, GamepadAxisType::RightStickX).into(),
+            GamepadAxis::new(gamepad, GamepadAxisType::RightStickY).into(),
+        )
+    }
+
+    /// Mouse wheel (x and y).
+    pub fn mouse_wheel() -> Self {
+        Self::new(
+            GenericAxis::MouseWheel(MouseAxis::X).into(),
+            GenericAxis::MouseWheel(MouseAxis::Y).into(),
+        )
+    }
+
+    /// Mouse motion (x and y).
+    pub fn mouse_motion() -> Self {
+        Self::new(
+            GenericAxis::MouseMotion(MouseAxis::X).into(),
+            GenericAxis::MouseMotion(MouseAxis::Y).into(),
+        )
+    }
+
+    /// Set the radial dead zone.
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+
+    /// Set the sensitivity of both axes.
+    pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.x.sensitivity = sensitivity;
+        self.y.sensitivity = sensitivity;
+        self
+    }
+
+    /// Invert the vertical axis.
+    pub fn inverted_y(mut self) -> Self {
+        self.y = self.y.inverted();
+        self
+    }
+
+    /// Resolve device independent gamepad axes to the axes of `gamepad`.
+    pub fn for_gamepad(mut self, gamepad: Gamepad) -> Self {
+        self.x = self.x.for_gamepad(gamepad);
+        self.y = self.y.for_gamepad(gamepad);
+        self
+    }
+
+    /// Value after applying the radial dead zone and the settings of both axes.
+    /// If an optional input is omitted it returns 0. for that axis.
+    pub fn value(
+        &self,
+        gamepad_axes: Option<&Axis<GamepadAxis>>,
+        gamepad_buttons: Option<&Axis<GamepadButton>>,
+        mouse_axes: Option<&MouseAxisInput>,
+    ) -> Vec2 {
+        let raw = Vec2::new(
+            self.x.axis.value(gamepad_axes, gamepad_buttons, mouse_axes),
+            self.y.axis.value(gamepad_axes, gamepad_buttons, mouse_axes),
+        );
+        if raw.length() <= self.dead_zone {
+            return Vec2::ZERO;
+        }
+        Vec2::new(self.x.apply(raw.x), self.y.apply(raw.y))
+    }
+}
+
+/// Maps an action to any amount of axes.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MappedAxes<A>
+where
+    A: InputAction + 'static,
+{
+    /// Action of this mapping.
+    pub(crate) action: A,
+    /// List of axes it maps to.
+    pub(crate) axes: SmallVec<[AxisBinding; BUTTON_COUNT]>,
+}
+
+impl<A: InputAction> MappedAxes<A> {
+    /// Simpel Constructor
+    pub fn new(action: A, axes: &[AxisBinding]) -> Self {
+        Self {
+            action,
+            axes: SmallVec::from_slice(axes),
+        }
+    }
+
+    /// Initializes `MappedAxes<A>` with only a single mapping to `axis`
+    pub fn new_single(action: A, axis: AxisBinding) -> Self {
+        Self::new(action, &[axis])
+    }
+
+    /// List of axes the action is mapped to.
+    pub fn get_axes(&self) -> &[AxisBinding] {
+        &self.axes
+    }
+
+    /// Mapped action.
+    pub fn get_action(&self) -> A {
+        self.action
+    }
+}
+
+/// Maps an action to any amount of dual axes.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MappedDualAxes<A>
+where
+    A: InputAction + 'static,
+{
+    /// Action of this mapping.
+    pub(crate) action: A,
+    /// List of dual axes it maps to.
+    pub(crate) axes: SmallVec<[DualAxisBinding; BUTTON_COUNT]>,
+}
+
+impl<A: InputAction> MappedDualAxes<A> {
+    /// Simpel Constructor
+    pub fn new(action: A, axes: &[DualAxisBinding]) -> Self {
+        Self {
+            action,
+            axes: SmallVec::from_slice(axes),
+        }
+    }
+
+    /// Initializes `MappedDualAxes<A>` with only a single mapping to `axes`
+    pub fn new_single(action: A, axes: DualAxisBinding) -> Self {
+        Self::new(action, &[axes])
+    }
+
+    /// List of dual axes the action is mapped to.
+    pub fn get_axes(&self) -> &[DualAxisBinding] {
+        &self.axes
+    }
+
+    /// Mapped action.
+    pub fn get_action(&self) -> A {
+        self.action
+    }
+}
@@ -0,0 +1,300 @@
+//! Chords (Ctrl+S, Shift+LMB) and sequences (double tap) as bindings
+//!
+//! A pressed chord suppresses every binding it contains, so Ctrl+S does not also trigger S.
+//! With `InputContexts` only chords of active contexts suppress other bindings.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_tarot_chariot::{ButtonChord, ButtonMapping, InputAction, InputContext, InputContexts, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     PanDown,
+//!     Save,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::PanDown, KeyCode::KeyS.into()));
+//!         button_mapping.insert_mapping(MappedButtons::new_chord(
+//!             SimpleInputAction::Save,
+//!             ButtonChord::with_modifier(KeyCode::ControlLeft, KeyCode::KeyS.into()),
+//!         ).in_context("editor"));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mapping = SimpleInputAction::default_mapping();
+//!     let mut input = ButtonInput::default();
+//!     input.press(KeyCode::ControlRight);
+//!     input.press(KeyCode::KeyS);
+//!     assert!(mapping.just_pressed(&SimpleInputAction::Save, Some(&input), None, None));
+//!     assert!(!mapping.pressed(&SimpleInputAction::PanDown, Some(&input), None, None));
+//!     let mut contexts = InputContexts::default();
+//!     assert!(mapping.pressed_in(&SimpleInputAction::PanDown, &contexts, Some(&input), None, None));
+//!     contexts.push(InputContext::new("editor"));
+//!     assert!(!mapping.pressed_in(&SimpleInputAction::PanDown, &contexts, Some(&input), None, None));
+//!     // The modifier may also be pressed last.
+//!     let mut input = ButtonInput::default();
+//!     input.press(KeyCode::KeyS);
+//!     input.clear();
+//!     input.press(KeyCode::ControlLeft);
+//!     assert!(mapping.just_pressed(&SimpleInputAction::Save, Some(&input), None, None));
+//! }
+//! ```
+
+use crate::{GenericButton, BUTTON_COUNT};
+use bevy_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::time::Duration;
+
+/// Other side of a modifier key (e.g. `ControlRight` for `ControlLeft`).
+pub fn modifier_counterpart(key: KeyCode) -> Option<KeyCode> {
+    use KeyCode::*;
+    match key {
+        ControlLeft => Some(ControlRight),
+        ControlRight => Some(ControlLeft),
+        ShiftLeft => Some(ShiftRight),
+        ShiftRight => Some(ShiftLeft),
+        AltLeft => Some(AltRight),
+        AltRight => Some(AltLeft),
+        SuperLeft => Some(SuperRight),
+        SuperRight => Some(SuperLeft),
+        _ => None,
+    }
+}
+
+/// Check if `key` is a modifier (Control, Shift, Alt or Super).
+pub fn is_modifier(key: KeyCode) -> bool {
+    modifier_counterpart(key).is_some()
+}
+
+/// Check if `a` and `b` are the same button, treating left and right modifiers as equal unless `strict_sides` is set.
+pub fn buttons_match(a: &GenericButton, b: &GenericButton, strict_sides: bool) -> bool {
+    if a == b {
+        return true;
+    }
+    match (a, b, strict_sides) {
+        (GenericButton::KeyBoard(a), GenericButton::KeyBoard(b), false) => {
+            modifier_counterpart(*a) == Some(*b)
+        }
+        _ => false,
+    }
+}
+
+/// Several buttons that have to be pressed together.
+/// The chord fires when the last missing button is pressed, in any order.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ButtonChord {
+    /// Buttons of the chord, modifiers first.
+    buttons: SmallVec<[GenericButton; BUTTON_COUNT]>,
+    /// Distinguish between left and right modifiers.
+    #[serde(default)]
+    strict_sides: bool,
+}
+
+impl ButtonChord {
+    /// Chord of all `buttons`. List modifiers first, the order is only used for labels.
+    pub fn new(buttons: &[GenericButton]) -> Self {
+        Self {
+            buttons: SmallVec::from_slice(buttons),
+            strict_sides: false,
+        }
+    }
+
+    /// Chord of `modifier` + `button`.
+    pub fn with_modifier(modifier: KeyCode, button: GenericButton) -> Self {
+        Self::new(&[modifier.into(), button])
+    }
+
+    /// Distinguish between left and right modifiers.
+    pub fn strict_sides(mut self) -> Self {
+        self.strict_sides = true;
+        self
+    }
+
+    /// Buttons of the chord.
+    pub fn get_buttons(&self) -> &[GenericButton] {
+        &self.buttons
+    }
+
+    /// Check if the chord uses `button` (respecting side equivalence).
+    pub fn contains(&self, button: &GenericButton) -> bool {
+        self.buttons
+            .iter()
+            .any(|b| buttons_match(b, button, self.strict_sides))
+    }
+
+    /// Check if both chords use the same buttons in any order.
+    /// Left and right modifiers are equal unless both chords distinguish sides.
+    pub fn same_buttons(&self, other: &ButtonChord) -> bool {
+        let strict_sides = self.strict_sides && other.strict_sides;
+        let covers = |a: &[GenericButton], b: &[GenericButton]| {
+            a.iter()
+                .all(|x| b.iter().any(|y| buttons_match(x, y, strict_sides)))
+        };
+        self.buttons.len() == other.buttons.len()
+            && covers(&self.buttons, &other.buttons)
+            && covers(&other.buttons, &self.buttons)
+    }
+
+    /// Check if every button of `other` is part of this chord.
+    pub fn contains_all(&self, other: &[GenericButton]) -> bool {
+        other.iter().all(|b| self.contains(b))
+    }
+
+    /// Check if all buttons of the chord are pressed.
+    /// If an optional ButtonInput is omitted it returns false for that button.
+    pub fn pressed(
+        &self,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> bool {
+        !self.buttons.is_empty()
+            && self.buttons.iter().all(|b| {
+                self.side_variants(b)
+                    .any(|b| b.pressed(key_codes, mouse_buttons, gamepad_buttons))
+            })
+    }
+
+    /// Check if the chord was completed in this cycle.
+    /// If an optional ButtonInput is omitted it returns false for that button.
+    pub fn just_pressed(
+        &self,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> bool {
+        self.pressed(key_codes, mouse_buttons, gamepad_buttons)
+            && self.buttons.iter().any(|b| {
+                self.side_variants(b)
+                    .any(|b| b.just_pressed(key_codes, mouse_buttons, gamepad_buttons))
+            })
+    }
+
+    /// Check if the chord was broken in this cycle.
+    /// If an optional ButtonInput is omitted it returns false for that button.
+    pub fn just_released(
+        &self,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> bool {
+        !self.pressed(key_codes, mouse_buttons, gamepad_buttons)
+            && self.buttons.iter().any(|b| {
+                self.side_variants(b)
+                    .any(|b| b.just_released(key_codes, mouse_buttons, gamepad_buttons))
+            })
+            && self.buttons.iter().all(|b| {
+                self.side_variants(b).any(|b| {
+                    b.pressed(key_codes, mouse_buttons, gamepad_buttons)
+                        || b.just_released(key_codes, mouse_buttons, gamepad_buttons)
+                })
+            })
+    }
+
+    /// Resolve device independent gamepad buttons to the buttons of `gamepad`.
+    pub fn for_gamepad(mut self, gamepad: Gamepad) -> Self {
+        self.buttons.iter_mut().for_each(|b| *b = b.for_gamepad(gamepad));
+        self
+    }
+
+    /// `button` and, if sides are not strict, its modifier counterpart.
+    fn side_variants<'a>(
+        &self,
+        button: &'a GenericButton,
+    ) -> impl Iterator<Item = GenericButton> + 'a {
+        let counterpart = match (button, self.strict_sides) {
+            (GenericButton::KeyBoard(k), false) => modifier_counterpart(*k).map(GenericButton::from),
+            _ => None,
+        };
+        std::iter::once(*button).chain(counterpart)
+    }
+}
+
+/// Buttons that have to be pressed one after another, e.g. a double tap.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ButtonSequence {
+    /// Buttons in the order they have to be pressed.
+    buttons: SmallVec<[GenericButton; BUTTON_COUNT]>,
+    /// Maximum time between two presses.
+    max_interval: Duration,
+}
+
+impl ButtonSequence {
+    /// Sequence of `buttons` with at most `max_interval` between two presses.
+    pub fn new(buttons: &[GenericButton], max_interval: Duration) -> Self {
+        Self {
+            buttons: SmallVec::from_slice(buttons),
+            max_interval,
+        }
+    }
+
+    /// Pressing `button` twice within `max_interval`.
+    pub fn double_tap(button: GenericButton, max_interval: Duration) -> Self {
+        Self::new(&[button, button], max_interval)
+    }
+
+    /// Buttons of the sequence.
+    pub fn get_buttons(&self) -> &[GenericButton] {
+        &self.buttons
+    }
+
+    /// Maximum time between two presses.
+    pub fn max_interval(&self) -> Duration {
+        self.max_interval
+    }
+
+    /// Resolve device independent gamepad buttons to the buttons of `gamepad`.
+    pub fn for_gamepad(mut self, gamepad: Gamepad) -> Self {
+        self.buttons.iter_mut().for_each(|b| *b = b.for_gamepad(gamepad));
+        self
+    }
+
+    /// Advance `progress` by one frame. Returns true in the frame the sequence is completed.
+    /// If an optional ButtonInput is omitted it returns false for that button.
+    pub fn advance(
+        &self,
+        progress: &mut SequenceProgress,
+        delta: Duration,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> bool {
+        if self.buttons.is_empty() {
+            return false;
+        }
+        progress.elapsed += delta;
+        if progress.step > 0 && progress.elapsed > self.max_interval {
+            *progress = SequenceProgress::default();
+        }
+        let just_pressed = |b: &GenericButton| b.just_pressed(key_codes, mouse_buttons, gamepad_buttons);
+        if just_pressed(&self.buttons[progress.step]) {
+            progress.step += 1;
+            progress.elapsed = Duration::ZERO;
+        } else if self.buttons.iter().any(just_pressed) {
+            // Wrong button, this might still be the start of a new sequence
+            progress.step = usize::from(just_pressed(&self.buttons[0]));
+            progress.elapsed = Duration::ZERO;
+        }
+        if progress.step == self.buttons.len() {
+            *progress = SequenceProgress::default();
+            return true;
+        }
+        false
+    }
+}
+
+/// How far a `ButtonSequence` has been entered.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SequenceProgress {
+    /// Index of the next button.
+    pub step: usize,
+    /// Time since the last correct press.
+    pub elapsed: Duration,
+}
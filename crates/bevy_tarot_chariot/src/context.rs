@@ -0,0 +1,135 @@
+//! Input contexts that decide which mappings see a button
+//!
+//! Mappings without a context are always active, but below every pushed context.
+//! A consuming context hides its buttons from all contexts below it.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_tarot_chariot::{ButtonMapping, InputAction, InputContext, InputContexts, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Deselect,
+//!     OpenMenu,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::OpenMenu, KeyCode::Escape.into()));
+//!         button_mapping.insert_mapping(
+//!             MappedButtons::new_single(SimpleInputAction::Deselect, KeyCode::Escape.into()).in_context("selection"),
+//!         );
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mapping = SimpleInputAction::default_mapping();
+//!     let mut contexts = InputContexts::default();
+//!     let mut input = ButtonInput::default();
+//!     input.press(KeyCode::Escape);
+//!     assert!(mapping.pressed_in(&SimpleInputAction::OpenMenu, &contexts, Some(&input), None, None));
+//!     assert!(!mapping.pressed_in(&SimpleInputAction::Deselect, &contexts, Some(&input), None, None));
+//!     contexts.push(InputContext::new("selection").consuming());
+//!     assert!(!mapping.pressed_in(&SimpleInputAction::OpenMenu, &contexts, Some(&input), None, None));
+//!     assert!(mapping.pressed_in(&SimpleInputAction::Deselect, &contexts, Some(&input), None, None));
+//! }
+//! ```
+
+use crate::InputAction;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// A named layer of mappings.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputContext {
+    /// Name that mappings refer to.
+    pub name: String,
+    /// Contexts with a higher priority see buttons first. Equal priorities are ordered by the stack.
+    #[serde(default)]
+    pub priority: i32,
+    /// Hide buttons this context maps from contexts below it.
+    #[serde(default)]
+    pub consume: bool,
+}
+
+impl InputContext {
+    /// Non consuming context with priority 0.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            priority: 0,
+            consume: false,
+        }
+    }
+
+    /// Set the priority.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Consume buttons that this context maps.
+    pub fn consuming(mut self) -> Self {
+        self.consume = true;
+        self
+    }
+}
+
+/// Stack of active input contexts for actions of type `A`.
+#[derive(Resource, Clone, Debug)]
+pub struct InputContexts<A: InputAction> {
+    /// Active contexts, the last one is the top.
+    stack: Vec<InputContext>,
+    _action_dummy: PhantomData<A>,
+}
+
+impl<A: InputAction> Default for InputContexts<A> {
+    fn default() -> Self {
+        Self {
+            stack: vec![],
+            _action_dummy: PhantomData,
+        }
+    }
+}
+
+impl<A: InputAction> InputContexts<A> {
+    /// Push a context on top. A context with the same name is removed first.
+    pub fn push(&mut self, context: InputContext) {
+        self.remove(&context.name);
+        self.stack.push(context);
+    }
+
+    /// Pop the top context.
+    pub fn pop(&mut self) -> Option<InputContext> {
+        self.stack.pop()
+    }
+
+    /// Remove the context with `name` wherever it is in the stack.
+    pub fn remove(&mut self, name: &str) -> Option<InputContext> {
+        self.stack
+            .iter()
+            .position(|c| c.name == name)
+            .map(|i| self.stack.remove(i))
+    }
+
+    /// Check if the context with `name` is on the stack.
+    pub fn is_active(&self, name: &str) -> bool {
+        self.stack.iter().any(|c| c.name == name)
+    }
+
+    /// Top context of the stack.
+    pub fn top(&self) -> Option<&InputContext> {
+        self.stack.last()
+    }
+
+    /// Active contexts in the order they see buttons (highest priority first, then top of stack first).
+    pub fn ordered(&self) -> Vec<&InputContext> {
+        let mut result = self.stack.iter().rev().collect::<Vec<_>>();
+        result.sort_by_key(|c| std::cmp::Reverse(c.priority));
+        result
+    }
+}
@@ -0,0 +1,20 @@
+//! Errors of this crate
+
+use thiserror::Error;
+
+/// Errors when saving or loading bindings.
+#[derive(Error, Debug)]
+pub enum ChariotError {
+    /// File could not be read or written.
+    #[error("Could not access bindings file: {0}")]
+    Io(#[from] std::io::Error),
+    /// Bindings could not be parsed.
+    #[error("Could not parse bindings: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    /// Bindings could not be serialized.
+    #[error("Could not serialize bindings: {0}")]
+    Serialize(#[from] ron::Error),
+    /// Bindings file was written by a newer version.
+    #[error("Unsupported bindings version {0}")]
+    UnsupportedVersion(u32),
+}
@@ -0,0 +1,101 @@
+//! Observer events for action transitions
+//!
+//! `trigger_action_events::<A>` reads `ActionState<A>` and triggers `ActionPressed<A>`, `ActionHeld<A>`
+//! and `ActionReleased<A>` for observers. `action_event_plugin::<A>` runs it every frame,
+//! or it can be added manually with a run condition.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_app::prelude::*;
+//! use bevy_ecs::prelude::*;
+//! use bevy_time::Time;
+//! use bevy_tarot_chariot::{ActionPressed, ButtonMapping, InputAction, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::{prelude::*, InputPlugin};
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Jump, KeyCode::Space.into()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! #[derive(Resource, Default)]
+//! struct Jumps(usize);
+//!
+//! pub fn main() {
+//!     let mut app = App::new();
+//!     app.add_plugins(InputPlugin);
+//!     app.init_resource::<Time>();
+//!     app.init_resource::<Jumps>();
+//!     app.add_plugins(bevy_tarot_chariot::plugin::<SimpleInputAction>);
+//!     app.add_plugins(bevy_tarot_chariot::action_event_plugin::<SimpleInputAction>);
+//!     app.observe(|trigger: Trigger<ActionPressed<SimpleInputAction>>, mut jumps: ResMut<Jumps>| {
+//!         if trigger.event().action == SimpleInputAction::Jump {
+//!             jumps.0 += 1;
+//!         }
+//!     });
+//!     app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+//!     app.update();
+//!     app.update();
+//!     assert_eq!(app.world().resource::<Jumps>().0, 1);
+//! }
+//! ```
+
+use crate::{ActionState, ActionStateSystem, InputAction};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use std::time::Duration;
+
+/// Triggers action events for `A` after `ActionState<A>` is updated.
+pub fn action_event_plugin<A: InputAction>(app: &mut App) {
+    app.add_systems(
+        PreUpdate,
+        trigger_action_events::<A>.after(ActionStateSystem),
+    );
+}
+
+/// `action` was pressed this frame.
+#[derive(Event, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ActionPressed<A: InputAction> {
+    /// Pressed action
+    pub action: A,
+}
+
+/// `action` is still pressed, triggered every frame after `ActionPressed`.
+#[derive(Event, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ActionHeld<A: InputAction> {
+    /// Held action
+    pub action: A,
+    /// How long the action has been held.
+    pub duration: Duration,
+}
+
+/// `action` was released this frame.
+#[derive(Event, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ActionReleased<A: InputAction> {
+    /// Released action
+    pub action: A,
+}
+
+/// Triggers `ActionPressed<A>`, `ActionHeld<A>` and `ActionReleased<A>` from `ActionState<A>`.
+pub fn trigger_action_events<A: InputAction>(mut commands: Commands, state: Res<ActionState<A>>) {
+    for (action, data) in state.iter() {
+        let action = *action;
+        if data.just_pressed {
+            commands.trigger(ActionPressed { action });
+        } else if data.pressed {
+            commands.trigger(ActionHeld {
+                action,
+                duration: data.held_duration,
+            });
+        } else if data.just_released {
+            commands.trigger(ActionReleased { action });
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Button prompt glyphs
+//!
+//! `ButtonGlyphs<K>` resolves buttons to sprite keys (`K` is usually a magician `SpriteAssetKey`).
+//! Keyboard, mouse and touch buttons use the keyboard set, gamepad buttons the set of the active `GamepadStyle`.
+//! With the `magician` feature a `Glyph` can be turned into an `AddSpriteToEntity` trigger.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_tarot_chariot::{ButtonChord, ButtonGlyphs, ButtonMapping, GamepadStyle, GenericButton, Glyph, GlyphSet, InputAction, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Save,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         let chord = ButtonChord::new(&[KeyCode::ControlLeft.into(), KeyCode::KeyS.into()]);
+//!         button_mapping.insert_mapping(MappedButtons::new_chord(SimpleInputAction::Save, chord));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! let mut glyphs = ButtonGlyphs::<String>::default();
+//! glyphs.set_keyboard(GlyphSet::default().with_glyph(KeyCode::KeyE.into(), Glyph::new("keys".to_string()).with_index(4)));
+//! glyphs.set_gamepad(
+//!     GamepadStyle::Xbox,
+//!     GlyphSet::default().with_glyph(GamepadButtonType::South.into(), Glyph::new("xbox_a".to_string())),
+//! );
+//! glyphs.style = GamepadStyle::Xbox;
+//! assert_eq!(glyphs.glyph(&KeyCode::KeyE.into()).unwrap().index, Some(4));
+//! let south = GamepadButton::new(Gamepad::new(1), GamepadButtonType::South);
+//! assert_eq!(glyphs.glyph(&south.into()).unwrap().key, "xbox_a");
+//!
+//! let mapping = SimpleInputAction::default_mapping();
+//! assert!(glyphs.action_glyphs(&mapping, &SimpleInputAction::Save).is_empty());
+//! glyphs.set_keyboard(
+//!     GlyphSet::default()
+//!         .with_glyph(KeyCode::ControlLeft.into(), Glyph::new("keys".to_string()).with_index(1))
+//!         .with_glyph(KeyCode::KeyS.into(), Glyph::new("keys".to_string()).with_index(2)),
+//! );
+//! let save = glyphs.action_glyphs(&mapping, &SimpleInputAction::Save);
+//! assert_eq!(save.len(), 1);
+//! assert_eq!(save[0].iter().map(|g| g.index).collect::<Vec<_>>(), [Some(1), Some(2)]);
+//! ```
+
+use crate::{Binding, ButtonMapping, GamepadStyle, GenericButton, InputAction};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Sprite of a button prompt.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Glyph<K> {
+    /// Key of the sprite or sprite sheet.
+    pub key: K,
+    /// Index in the sprite sheet.
+    #[serde(default)]
+    pub index: Option<usize>,
+}
+
+impl<K> Glyph<K> {
+    /// Glyph that is a single sprite.
+    pub fn new(key: K) -> Self {
+        Self { key, index: None }
+    }
+
+    /// Use the sprite at `index` of a sprite sheet.
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+}
+
+#[cfg(feature = "magician")]
+impl<K: bevy_tarot_magician::SpriteAssetKey> Glyph<K> {
+    /// Trigger event that adds the glyph sprite to `entity`.
+    pub fn add_to_entity(
+        &self,
+        entity: Entity,
+    ) -> bevy_tarot_magician::sprite::AddSpriteToEntity<K> {
+        bevy_tarot_magician::sprite::AddSpriteToEntity {
+            entity,
+            key: self.key.clone(),
+            index: self.index,
+            name: None,
+        }
+    }
+}
+
+/// Glyphs of a single style. Gamepad buttons are stored independent of the device.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GlyphSet<K> {
+    /// Glyph per button.
+    glyphs: HashMap<GenericButton, Glyph<K>>,
+}
+
+impl<K> Default for GlyphSet<K> {
+    fn default() -> Self {
+        Self {
+            glyphs: HashMap::new(),
+        }
+    }
+}
+
+impl<K> GlyphSet<K> {
+    /// Add the glyph of `button`.
+    pub fn with_glyph(mut self, button: GenericButton, glyph: Glyph<K>) -> Self {
+        self.insert(button, glyph);
+        self
+    }
+
+    /// Set the glyph of `button`.
+    pub fn insert(&mut self, button: GenericButton, glyph: Glyph<K>) {
+        self.glyphs.insert(device_independent(button), glyph);
+    }
+
+    /// Glyph of `button`.
+    pub fn get(&self, button: &GenericButton) -> Option<&Glyph<K>> {
+        self.glyphs.get(&device_independent(*button))
+    }
+}
+
+/// Glyph sets for keyboard and mouse and each gamepad style.
+#[derive(Resource, Clone, Debug)]
+pub struct ButtonGlyphs<K: Send + Sync + 'static> {
+    /// Glyphs of keyboard keys, mouse buttons and touch buttons.
+    keyboard: GlyphSet<K>,
+    /// Glyphs of gamepad buttons per style.
+    gamepad: HashMap<GamepadStyle, GlyphSet<K>>,
+    /// Active gamepad style.
+    pub style: GamepadStyle,
+}
+
+impl<K: Send + Sync + 'static> Default for ButtonGlyphs<K> {
+    fn default() -> Self {
+        Self {
+            keyboard: GlyphSet::default(),
+            gamepad: HashMap::new(),
+            style: GamepadStyle::default(),
+        }
+    }
+}
+
+impl<K: Send + Sync + 'static> ButtonGlyphs<K> {
+    /// Set the glyphs of keyboard keys, mouse buttons and touch buttons.
+    pub fn set_keyboard(&mut self, glyphs: GlyphSet<K>) {
+        self.keyboard = glyphs;
+    }
+
+    /// Set the glyphs of gamepad buttons in `style`.
+    pub fn set_gamepad(&mut self, style: GamepadStyle, glyphs: GlyphSet<K>) {
+        self.gamepad.insert(style, glyphs);
+    }
+
+    /// Glyph of `button`. Gamepad buttons fall back to `GamepadStyle::Generic`.
+    pub fn glyph(&self, button: &GenericButton) -> Option<&Glyph<K>> {
+        match button {
+            GenericButton::KeyBoard(_) | GenericButton::Mouse(_) | GenericButton::Touch(_) => {
+                self.keyboard.get(button)
+            }
+            GenericButton::Gamepad(_) | GenericButton::AnyGamepad(_) => self
+                .gamepad
+                .get(&self.style)
+                .and_then(|s| s.get(button))
+                .or_else(|| {
+                    self.gamepad
+                        .get(&GamepadStyle::Generic)
+                        .and_then(|s| s.get(button))
+                }),
+        }
+    }
+
+    /// Glyphs of the buttons of `binding`, `None` if a button has no glyph.
+    pub fn binding_glyphs(&self, binding: &Binding) -> Option<Vec<&Glyph<K>>> {
+        match binding {
+            Binding::Button(b) => self.glyph(b).map(|g| vec![g]),
+            Binding::Chord(c) => c.get_buttons().iter().map(|b| self.glyph(b)).collect(),
+        }
+    }
+
+    /// Glyphs of every binding of `action`, one group per button or chord (see `ButtonMapping::bindings`).
+    /// Bindings with a button without glyph are skipped.
+    pub fn action_glyphs<A: InputAction>(
+        &self,
+        mapping: &ButtonMapping<A>,
+        action: &A,
+    ) -> Vec<Vec<&Glyph<K>>> {
+        mapping
+            .bindings(action)
+            .iter()
+            .filter_map(|b| self.binding_glyphs(b))
+            .collect()
+    }
+}
+
+/// `button` with gamepad buttons of a specific device replaced by `GenericButton::AnyGamepad`.
+fn device_independent(button: GenericButton) -> GenericButton {
+    match button {
+        GenericButton::Gamepad(b) => GenericButton::AnyGamepad(b.button_type),
+        _ => button,
+    }
+}
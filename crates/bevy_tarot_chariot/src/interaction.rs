@@ -0,0 +1,167 @@
+//! Hold, tap, double tap and charge detection
+//!
+//! Thresholds are configured per `MappedButtons` with `Interactions` and evaluated into `ActionState<A>`.
+//!
+//! # Example usage:
+//! ```
+//! use std::time::Duration;
+//! use serde::Serialize;
+//! use bevy_app::prelude::*;
+//! use bevy_time::Time;
+//! use bevy_tarot_chariot::{ActionState, ButtonMapping, InputAction, Interactions, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::{prelude::*, InputPlugin};
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(
+//!             MappedButtons::new_single(SimpleInputAction::Jump, KeyCode::Space.into())
+//!                 .with_interactions(Interactions::default().with_tap(Duration::from_millis(200))),
+//!         );
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mut app = App::new();
+//!     app.add_plugins(InputPlugin);
+//!     app.init_resource::<Time>();
+//!     app.add_plugins(bevy_tarot_chariot::plugin::<SimpleInputAction>);
+//!     for _ in 0..2 {
+//!         app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+//!         app.update();
+//!         app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::Space);
+//!         app.update();
+//!         assert!(app.world().resource::<ActionState<SimpleInputAction>>().tapped(&SimpleInputAction::Jump));
+//!     }
+//!     let state = app.world().resource::<ActionState<SimpleInputAction>>();
+//!     assert!(state.double_tapped(&SimpleInputAction::Jump));
+//!     assert!(!state.held(&SimpleInputAction::Jump));
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Thresholds for the interactions of a mapping.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Interactions {
+    /// Time until a press counts as held.
+    pub hold: Duration,
+    /// Maximum press duration of a tap.
+    pub tap: Duration,
+    /// Maximum time between two taps of a double tap.
+    pub double_tap: Duration,
+    /// Time until the charge is full.
+    pub charge: Duration,
+}
+
+impl Default for Interactions {
+    fn default() -> Self {
+        Self {
+            hold: Duration::from_millis(500),
+            tap: Duration::from_millis(200),
+            double_tap: Duration::from_millis(300),
+            charge: Duration::from_secs(1),
+        }
+    }
+}
+
+impl Interactions {
+    /// Set the time until a press counts as held.
+    pub fn with_hold(mut self, hold: Duration) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Set the maximum press duration of a tap.
+    pub fn with_tap(mut self, tap: Duration) -> Self {
+        self.tap = tap;
+        self
+    }
+
+    /// Set the maximum time between two taps of a double tap.
+    pub fn with_double_tap(mut self, double_tap: Duration) -> Self {
+        self.double_tap = double_tap;
+        self
+    }
+
+    /// Set the time until the charge is full.
+    pub fn with_charge(mut self, charge: Duration) -> Self {
+        self.charge = charge;
+        self
+    }
+}
+
+/// Interactions of a single action in the current frame.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InteractionState {
+    /// Pressed for at least `Interactions::hold`.
+    pub held: bool,
+    /// `held` changed to true this frame.
+    pub just_held: bool,
+    /// Released this frame after a press shorter than `Interactions::tap`.
+    pub tapped: bool,
+    /// Tapped this frame within `Interactions::double_tap` of the previous tap.
+    pub double_tapped: bool,
+    /// Charge level from 0 to 1 while pressed.
+    pub charge: f32,
+    /// Charge level at release, only set in the frame the action is released.
+    pub released_charge: f32,
+    /// Duration of the current press.
+    pressed_for: Option<Duration>,
+    /// Time since the last tap that can still become a double tap.
+    since_tap: Option<Duration>,
+}
+
+impl InteractionState {
+    /// Advance to the next frame.
+    pub fn update(&mut self, pressed: bool, delta: Duration, interactions: &Interactions) {
+        let pressed_for = self.pressed_for.map(|d| d + delta);
+        self.since_tap = self
+            .since_tap
+            .map(|d| d + delta)
+            .filter(|d| *d <= interactions.double_tap);
+        self.tapped = false;
+        self.double_tapped = false;
+        self.released_charge = 0.;
+        if pressed {
+            let pressed_for = pressed_for.unwrap_or_default();
+            let held = pressed_for >= interactions.hold;
+            self.just_held = held && !self.held;
+            self.held = held;
+            self.charge = charge_level(pressed_for, interactions.charge);
+            self.pressed_for = Some(pressed_for);
+            return;
+        }
+        if let Some(pressed_for) = pressed_for {
+            self.released_charge = self.charge;
+            self.tapped = pressed_for <= interactions.tap;
+            if self.tapped {
+                self.double_tapped = self.since_tap.is_some();
+                self.since_tap = if self.double_tapped {
+                    None
+                } else {
+                    Some(Duration::ZERO)
+                };
+            }
+        }
+        self.held = false;
+        self.just_held = false;
+        self.charge = 0.;
+        self.pressed_for = None;
+    }
+}
+
+/// Charge after being pressed for `pressed_for`.
+fn charge_level(pressed_for: Duration, charge: Duration) -> f32 {
+    if charge.is_zero() {
+        return 1.;
+    }
+    (pressed_for.as_secs_f32() / charge.as_secs_f32()).min(1.)
+}
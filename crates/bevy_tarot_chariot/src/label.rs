@@ -0,0 +1,277 @@
+//! Display names of buttons and bindings
+//!
+//! Every button has a stable `label_key` (e.g. `key.KeyE`) that can be used for translation tables and
+//! an English default label. `ButtonLabels` overrides labels by key, e.g. with localized names.
+//!
+//! # Example usage:
+//! ```
+//! use bevy_tarot_chariot::{ButtonChord, ButtonLabels, GamepadStyle, GenericButton};
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! let interact = GenericButton::from(KeyCode::KeyE);
+//! assert_eq!(interact.label(GamepadStyle::Generic), "E");
+//! assert_eq!(interact.label_key(), "key.KeyE");
+//! assert_eq!(GenericButton::from(GamepadButtonType::South).label(GamepadStyle::PlayStation), "Cross");
+//! let save = ButtonChord::with_modifier(KeyCode::ControlLeft, KeyCode::KeyS.into());
+//! assert_eq!(save.label(GamepadStyle::Generic), "Ctrl+S");
+//! let mut labels = ButtonLabels::default();
+//! labels.insert("key.Space", "Leertaste");
+//! assert_eq!(labels.label(&KeyCode::Space.into(), GamepadStyle::Generic), "Leertaste");
+//! ```
+
+use crate::{Binding, ButtonChord, ButtonMapping, GenericButton, InputAction, TouchButton};
+use bevy_ecs::prelude::*;
+use bevy_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Naming (and glyph) style of gamepad buttons.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamepadStyle {
+    /// Position based names (South, East, ...)
+    #[default]
+    Generic,
+    /// A, B, X, Y
+    Xbox,
+    /// Cross, Circle, Square, Triangle
+    PlayStation,
+}
+
+impl GenericButton {
+    /// Stable identifier of the button, e.g. `key.KeyE`, `mouse.Left`, `gamepad.South` or `touch.SwipeLeft`.
+    pub fn label_key(&self) -> String {
+        match self {
+            GenericButton::KeyBoard(k) => format!("key.{:?}", k),
+            GenericButton::Mouse(m) => format!("mouse.{:?}", m),
+            GenericButton::Gamepad(b) => format!("gamepad.{:?}", b.button_type),
+            GenericButton::AnyGamepad(t) => format!("gamepad.{:?}", t),
+            GenericButton::Touch(TouchButton::Virtual(id)) => format!("touch.Virtual{}", id),
+            GenericButton::Touch(t) => format!("touch.{:?}", t),
+        }
+    }
+
+    /// English display name of the button.
+    pub fn label(&self, style: GamepadStyle) -> Cow<'static, str> {
+        match self {
+            GenericButton::KeyBoard(k) => key_label(*k),
+            GenericButton::Mouse(m) => mouse_label(*m),
+            GenericButton::Gamepad(b) => gamepad_label(b.button_type, style),
+            GenericButton::AnyGamepad(t) => gamepad_label(*t, style),
+            GenericButton::Touch(t) => touch_label(*t),
+        }
+    }
+}
+
+impl ButtonChord {
+    /// Labels of all buttons joined with `+`.
+    pub fn label(&self, style: GamepadStyle) -> String {
+        join_labels(self.get_buttons(), |b| b.label(style))
+    }
+}
+
+impl Binding {
+    /// Display name of the binding.
+    pub fn label(&self, style: GamepadStyle) -> String {
+        match self {
+            Binding::Button(b) => b.label(style).into_owned(),
+            Binding::Chord(c) => c.label(style),
+        }
+    }
+}
+
+impl<A: InputAction> ButtonMapping<A> {
+    /// Every button and chord of `action`, e.g. for "Press [E] to interact".
+    pub fn bindings(&self, action: &A) -> Vec<Binding> {
+        self.get_from_action(action)
+            .map(|m| {
+                m.get_buttons()
+                    .iter()
+                    .map(|b| Binding::Button(*b))
+                    .chain(m.get_chords().iter().cloned().map(Binding::Chord))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Display names of all bindings of `action`.
+    pub fn binding_labels(&self, action: &A, style: GamepadStyle) -> Vec<String> {
+        self.bindings(action)
+            .iter()
+            .map(|b| b.label(style))
+            .collect()
+    }
+}
+
+/// Label overrides by `GenericButton::label_key`, e.g. translations.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ButtonLabels {
+    /// Label per label key.
+    labels: HashMap<String, String>,
+}
+
+impl ButtonLabels {
+    /// Override the label of `label_key`.
+    pub fn insert(&mut self, label_key: impl Into<String>, label: impl Into<String>) {
+        self.labels.insert(label_key.into(), label.into());
+    }
+
+    /// Label of `button` overridden for `style` (`<label_key>.<style>`), overridden for all styles
+    /// (`<label_key>`) or its default label.
+    pub fn label(&self, button: &GenericButton, style: GamepadStyle) -> String {
+        let key = button.label_key();
+        match self.labels.get(&format!("{}.{:?}", key, style)) {
+            Some(label) => label.clone(),
+            None => self
+                .labels
+                .get(&key)
+                .cloned()
+                .unwrap_or_else(|| button.label(style).into_owned()),
+        }
+    }
+
+    /// Display name of `binding` using the overridden labels.
+    pub fn binding_label(&self, binding: &Binding, style: GamepadStyle) -> String {
+        match binding {
+            Binding::Button(b) => self.label(b, style),
+            Binding::Chord(c) => join_labels(c.get_buttons(), |b| self.label(b, style).into()),
+        }
+    }
+}
+
+fn join_labels(
+    buttons: &[GenericButton],
+    label: impl Fn(&GenericButton) -> Cow<'static, str>,
+) -> String {
+    buttons.iter().map(label).collect::<Vec<_>>().join("+")
+}
+
+/// English display name of `key`.
+pub fn key_label(key: KeyCode) -> Cow<'static, str> {
+    use KeyCode::*;
+    let label = match key {
+        ControlLeft | ControlRight => "Ctrl",
+        ShiftLeft | ShiftRight => "Shift",
+        AltLeft | AltRight => "Alt",
+        SuperLeft | SuperRight => "Super",
+        Escape => "Esc",
+        Backquote => "`",
+        Minus => "-",
+        Equal => "=",
+        BracketLeft => "[",
+        BracketRight => "]",
+        Backslash => "\\",
+        Semicolon => ";",
+        Quote => "'",
+        Comma => ",",
+        Period => ".",
+        Slash => "/",
+        ArrowUp => "Up",
+        ArrowDown => "Down",
+        ArrowLeft => "Left",
+        ArrowRight => "Right",
+        NumpadAdd => "Num +",
+        NumpadSubtract => "Num -",
+        NumpadMultiply => "Num *",
+        NumpadDivide => "Num /",
+        NumpadDecimal => "Num .",
+        Unidentified(_) => "Unknown",
+        _ => {
+            let name = format!("{:?}", key);
+            if let Some(rest) = name.strip_prefix("Key").or(name.strip_prefix("Digit")) {
+                return rest.to_string().into();
+            }
+            if let Some(rest) = name.strip_prefix("Numpad") {
+                return format!("Num {}", split_words(rest)).into();
+            }
+            return split_words(&name).into();
+        }
+    };
+    label.into()
+}
+
+/// English display name of `button`.
+pub fn mouse_label(button: MouseButton) -> Cow<'static, str> {
+    match button {
+        MouseButton::Left => "Left Mouse".into(),
+        MouseButton::Right => "Right Mouse".into(),
+        MouseButton::Middle => "Middle Mouse".into(),
+        MouseButton::Back => "Mouse Back".into(),
+        MouseButton::Forward => "Mouse Forward".into(),
+        MouseButton::Other(n) => format!("Mouse {}", n).into(),
+    }
+}
+
+/// English display name of `button` in `style`.
+pub fn gamepad_label(button: GamepadButtonType, style: GamepadStyle) -> Cow<'static, str> {
+    use GamepadButtonType::*;
+    use GamepadStyle::*;
+    let label = match (button, style) {
+        (South, Xbox) => "A",
+        (East, Xbox) => "B",
+        (North, Xbox) => "Y",
+        (West, Xbox) => "X",
+        (LeftTrigger, Xbox) => "LB",
+        (LeftTrigger2, Xbox) => "LT",
+        (RightTrigger, Xbox) => "RB",
+        (RightTrigger2, Xbox) => "RT",
+        (Select, Xbox) => "View",
+        (Start, Xbox) => "Menu",
+        (Mode, Xbox) => "Xbox",
+        (LeftThumb, Xbox) => "LS",
+        (RightThumb, Xbox) => "RS",
+        (South, PlayStation) => "Cross",
+        (East, PlayStation) => "Circle",
+        (North, PlayStation) => "Triangle",
+        (West, PlayStation) => "Square",
+        (LeftTrigger, PlayStation) => "L1",
+        (LeftTrigger2, PlayStation) => "L2",
+        (RightTrigger, PlayStation) => "R1",
+        (RightTrigger2, PlayStation) => "R2",
+        (Select, PlayStation) => "Share",
+        (Start, PlayStation) => "Options",
+        (Mode, PlayStation) => "PS",
+        (LeftThumb, PlayStation) => "L3",
+        (RightThumb, PlayStation) => "R3",
+        (South, Generic) => "South",
+        (East, Generic) => "East",
+        (North, Generic) => "North",
+        (West, Generic) => "West",
+        (LeftTrigger, Generic) => "Left Bumper",
+        (LeftTrigger2, Generic) => "Left Trigger",
+        (RightTrigger, Generic) => "Right Bumper",
+        (RightTrigger2, Generic) => "Right Trigger",
+        (Select, Generic) => "Select",
+        (Start, Generic) => "Start",
+        (Mode, Generic) => "Mode",
+        (LeftThumb, Generic) => "Left Stick",
+        (RightThumb, Generic) => "Right Stick",
+        (C, _) => "C",
+        (Z, _) => "Z",
+        (DPadUp, _) => "D-Pad Up",
+        (DPadDown, _) => "D-Pad Down",
+        (DPadLeft, _) => "D-Pad Left",
+        (DPadRight, _) => "D-Pad Right",
+        (Other(n), _) => return format!("Button {}", n).into(),
+    };
+    label.into()
+}
+
+/// English display name of `button`.
+pub fn touch_label(button: TouchButton) -> Cow<'static, str> {
+    match button {
+        TouchButton::Virtual(id) => format!("Touch Button {}", id).into(),
+        _ => split_words(&format!("{:?}", button)).into(),
+    }
+}
+
+/// `PageUp` -> `Page Up`
+fn split_words(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            result.push(' ');
+        }
+        result.push(c);
+    }
+    result
+}
@@ -37,7 +37,44 @@ use smallvec::SmallVec;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::time::Duration;
 pub use bevy_input;
+#[cfg(feature = "derive")]
+pub use bevy_tarot_chariot_derive::InputAction;
+use bevy_math::Vec2;
+
+mod action_state;
+mod axis;
+mod chord;
+mod context;
+mod error;
+mod events;
+mod glyph;
+mod interaction;
+mod label;
+mod persist;
+mod player;
+mod rebind;
+mod replay;
+mod simulate;
+mod touch;
+mod virtual_axis;
+pub use action_state::*;
+pub use axis::*;
+pub use chord::*;
+pub use context::*;
+pub use error::*;
+pub use events::*;
+pub use glyph::*;
+pub use interaction::*;
+pub use label::*;
+pub use persist::*;
+pub use player::*;
+pub use rebind::*;
+pub use replay::*;
+pub use simulate::*;
+pub use touch::*;
+pub use virtual_axis::*;
 
 macro_rules! define_button_count {
     ($value:expr) => {
@@ -47,9 +84,9 @@ macro_rules! define_button_count {
 }
 
 cfg_if::cfg_if! {
-    if #[cfg(feature = "4button")] {
+    if #[cfg(feature = "4buttons")] {
         define_button_count!(4);
-    } else if #[cfg(feature= "2button")] {
+    } else if #[cfg(feature = "2buttons")] {
         define_button_count!(2);
     } else if #[cfg(feature = "1button")] {
         define_button_count!(1);
@@ -58,8 +95,8 @@ cfg_if::cfg_if! {
     }
 }
 
-/// Generic abstraction over KeyBoard, Mouse and Gamepad Buttons
-/// TODO: What about sticks and stuff
+/// Generic abstraction over KeyBoard, Mouse, Gamepad and Touch Buttons
+/// Sticks, triggers and mouse axes are covered by `GenericAxis`.
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum GenericButton {
     /// Keyboard Input
@@ -68,6 +105,10 @@ pub enum GenericButton {
     Mouse(MouseButton),
     /// Gamepad button input
     Gamepad(GamepadButton),
+    /// Gamepad button on any gamepad, or on the player's gamepad (see `PlayerSlots`)
+    AnyGamepad(GamepadButtonType),
+    /// Virtual on-screen button or gesture (see `touch_plugin`)
+    Touch(TouchButton),
 }
 
 impl From<KeyCode> for GenericButton {
@@ -88,9 +129,22 @@ impl From<GamepadButton> for GenericButton {
     }
 }
 
+impl From<GamepadButtonType> for GenericButton {
+    fn from(value: GamepadButtonType) -> Self {
+        Self::AnyGamepad(value)
+    }
+}
+
+impl From<TouchButton> for GenericButton {
+    fn from(value: TouchButton) -> Self {
+        Self::Touch(value)
+    }
+}
+
 impl GenericButton {
     /// Check if the generic button is currently pressed.
     /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Touch buttons are checked with `touch_pressed`.
     pub fn pressed(
         &self,
         key_codes: Option<&ButtonInput<KeyCode>>,
@@ -103,11 +157,16 @@ impl GenericButton {
             GenericButton::Gamepad(b) => {
                 gamepad_buttons.map(|bi| bi.pressed(*b)).unwrap_or_default()
             }
+            GenericButton::AnyGamepad(t) => gamepad_buttons
+                .map(|bi| bi.get_pressed().any(|b| b.button_type == *t))
+                .unwrap_or_default(),
+            GenericButton::Touch(_) => false,
         }
     }
 
     /// Check if the generic button was pressed in this cycle.
     /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Always false for touch buttons, those are only evaluated by `ActionState`.
     pub fn just_pressed(
         &self,
         key_codes: Option<&ButtonInput<KeyCode>>,
@@ -124,11 +183,16 @@ impl GenericButton {
             GenericButton::Gamepad(b) => gamepad_buttons
                 .map(|bi| bi.just_pressed(*b))
                 .unwrap_or_default(),
+            GenericButton::AnyGamepad(t) => gamepad_buttons
+                .map(|bi| bi.get_just_pressed().any(|b| b.button_type == *t))
+                .unwrap_or_default(),
+            GenericButton::Touch(_) => false,
         }
     }
 
     /// Check if the generic button was released in this cycle.
     /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Always false for touch buttons, those are only evaluated by `ActionState`.
     pub fn just_released(
         &self,
         key_codes: Option<&ButtonInput<KeyCode>>,
@@ -145,13 +209,40 @@ impl GenericButton {
             GenericButton::Gamepad(b) => gamepad_buttons
                 .map(|bi| bi.just_released(*b))
                 .unwrap_or_default(),
+            GenericButton::AnyGamepad(t) => gamepad_buttons
+                .map(|bi| bi.get_just_released().any(|b| b.button_type == *t))
+                .unwrap_or_default(),
+            GenericButton::Touch(_) => false,
+        }
+    }
+
+    /// Check if the touch button is currently pressed. Always false for other buttons.
+    pub fn touch_pressed(&self, touch: Option<&ButtonInput<TouchButton>>) -> bool {
+        match self {
+            GenericButton::Touch(t) => touch.map(|bi| bi.pressed(*t)).unwrap_or_default(),
+            _ => false,
+        }
+    }
+
+    /// Resolve a device independent gamepad button to the button of `gamepad`.
+    pub fn for_gamepad(self, gamepad: Gamepad) -> Self {
+        match self {
+            GenericButton::AnyGamepad(t) => GamepadButton::new(gamepad, t).into(),
+            _ => self,
+        }
+    }
+
+    /// Gamepad of a button bound to a specific gamepad.
+    pub fn gamepad(&self) -> Option<Gamepad> {
+        match self {
+            GenericButton::Gamepad(b) => Some(b.gamepad),
+            _ => None,
         }
     }
 }
 
 /// Actions that respond to input (and are mapped) need to implement this trait.
-
-pub trait InputAction: Copy + Clone + Hash + Debug + Eq + Serialize {
+pub trait InputAction: Copy + Clone + Hash + Debug + Eq + Serialize + Send + Sync + 'static {
     /// Default mapping of the actions if it is not loaded manually.
     fn default_mapping() -> ButtonMapping<Self>;
 }
@@ -167,21 +258,76 @@ where
     action: A,
     /// List of Buttons it maps to. Currently optimized for 2 buttons.
     buttons: SmallVec<[GenericButton; BUTTON_COUNT]>,
+    /// Chords (e.g. Ctrl+S) that trigger the action.
+    #[serde(default)]
+    chords: SmallVec<[ButtonChord; 1]>,
+    /// Sequences (e.g. a double tap) that trigger the action for a single frame.
+    #[serde(default)]
+    sequences: SmallVec<[ButtonSequence; 1]>,
+    /// Name of the `InputContext` this mapping belongs to. `None` is always active.
+    #[serde(default)]
+    context: Option<String>,
+    /// Thresholds for hold, tap, double tap and charge.
+    #[serde(default)]
+    interactions: Interactions,
+    /// How long a press stays buffered in `ActionState`. `None` disables buffering.
+    #[serde(default)]
+    buffer: Option<Duration>,
 }
 
 impl<A: InputAction> MappedButtons<A> {
     /// Simpel Constructor
     pub fn new(action: A, buttons: &[GenericButton]) -> Self {
         let buttons = SmallVec::from_slice(buttons);
-        Self { action, buttons }
+        Self {
+            action,
+            buttons,
+            chords: SmallVec::new(),
+            sequences: SmallVec::new(),
+            context: None,
+            interactions: Interactions::default(),
+            buffer: None,
+        }
     }
 
     /// Initializes `MappedButton<A>` with only a single mapping to `button`
     pub fn new_single(action: A, button: GenericButton) -> Self {
-        Self {
-            action,
-            buttons: SmallVec::from_vec(vec![button]),
-        }
+        Self::new(action, &[button])
+    }
+
+    /// Initializes `MappedButton<A>` with only a single `chord`
+    pub fn new_chord(action: A, chord: ButtonChord) -> Self {
+        Self::new(action, &[]).with_chord(chord)
+    }
+
+    /// Add a chord that triggers the action.
+    pub fn with_chord(mut self, chord: ButtonChord) -> Self {
+        self.chords.push(chord);
+        self
+    }
+
+    /// Add a sequence that triggers the action.
+    pub fn with_sequence(mut self, sequence: ButtonSequence) -> Self {
+        self.sequences.push(sequence);
+        self
+    }
+
+    /// Only activate this mapping while the `InputContext` named `context` is active.
+    pub fn in_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Set the thresholds for hold, tap, double tap and charge.
+    pub fn with_interactions(mut self, interactions: Interactions) -> Self {
+        self.interactions = interactions;
+        self
+    }
+
+    /// Keep presses buffered for `window`, see `ActionState::consume_buffered`.
+    pub fn with_buffer(mut self, window: Duration) -> Self {
+        self.buffer = Some(window);
+        self
     }
 
     /// List of buttons the action is mapped to.
@@ -189,22 +335,72 @@ impl<A: InputAction> MappedButtons<A> {
         &self.buttons
     }
 
-    /// Mapped action.
+    /// Add a button or chord that triggers the action.
+    pub fn with_binding(mut self, binding: Binding) -> Self {
+        match binding {
+            Binding::Button(b) => self.buttons.push(b),
+            Binding::Chord(c) => self.chords.push(c),
+        }
+        self
+    }
+
+    /// Check if the action is mapped to `binding`. Chords match in any order (see `ButtonChord::same_buttons`).
+    pub fn has_binding(&self, binding: &Binding) -> bool {
+        match binding {
+            Binding::Button(b) => self.buttons.contains(b),
+            Binding::Chord(c) => self.chords.iter().any(|x| x.same_buttons(c)),
+        }
+    }
+
+    /// Chords the action is mapped to.
+    pub fn get_chords(&self) -> &[ButtonChord] {
+        &self.chords
+    }
+
+    /// Sequences the action is mapped to.
+    pub fn get_sequences(&self) -> &[ButtonSequence] {
+        &self.sequences
+    }
 
+    /// Mapped action.
     pub fn get_action(&self) -> A {
         self.action
     }
+
+    /// Name of the `InputContext` of this mapping.
+    pub fn get_context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    /// Thresholds for hold, tap, double tap and charge.
+    pub fn get_interactions(&self) -> &Interactions {
+        &self.interactions
+    }
+
+    /// Buffer window of presses.
+    pub fn get_buffer(&self) -> Option<Duration> {
+        self.buffer
+    }
 }
 
 /// Stores mappings of actions to buttons (and reverse)
 #[derive(Serialize, Deserialize, Resource, Clone, Debug)]
+#[serde(from = "MappingData<A>", into = "MappingData<A>")]
 pub struct ButtonMapping<A: InputAction + 'static> {
     /// Store MappedButtons
     mapped_buttons: Vec<MappedButtons<A>>,
     /// Map action to mapped buttons.
     from_action_map: HashMap<A, usize>,
     /// Map button to objects that map it.
-    from_button_map: HashMap<GenericButton, usize>,
+    from_button_map: HashMap<GenericButton, SmallVec<[usize; 2]>>,
+    /// Store MappedAxes
+    mapped_axes: Vec<MappedAxes<A>>,
+    /// Map action to mapped axes.
+    from_action_axis_map: HashMap<A, usize>,
+    /// Store MappedDualAxes
+    mapped_dual_axes: Vec<MappedDualAxes<A>>,
+    /// Map action to mapped dual axes.
+    from_action_dual_axis_map: HashMap<A, usize>,
 }
 
 impl<A: InputAction> Default for ButtonMapping<A> {
@@ -213,6 +409,10 @@ impl<A: InputAction> Default for ButtonMapping<A> {
             mapped_buttons: vec![],
             from_action_map: HashMap::new(),
             from_button_map: Default::default(),
+            mapped_axes: vec![],
+            from_action_axis_map: HashMap::new(),
+            mapped_dual_axes: vec![],
+            from_action_dual_axis_map: HashMap::new(),
         }
     }
 }
@@ -220,6 +420,7 @@ impl<A: InputAction> Default for ButtonMapping<A> {
 impl<A: InputAction> ButtonMapping<A> {
     /// Check if the `action` is currently pressed.
     /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Touch buttons are ignored, use `ActionState` for touch input.
     pub fn pressed(
         &self,
         action: &A,
@@ -227,16 +428,18 @@ impl<A: InputAction> ButtonMapping<A> {
         mouse_buttons: Option<&ButtonInput<MouseButton>>,
         gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
     ) -> bool {
-        self.get_buttons(action)
-            .map(|bts| {
-                bts.iter()
-                    .any(|b| b.pressed(key_codes, mouse_buttons, gamepad_buttons))
-            })
-            .unwrap_or_default()
+        self.evaluate(
+            action,
+            None,
+            |b| b.pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.pressed(key_codes, mouse_buttons, gamepad_buttons),
+        )
     }
 
     /// Check if the `action` was pressed in this cycle.
     /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Touch buttons are ignored, use `ActionState` for touch input.
     pub fn just_pressed(
         &self,
         action: &A,
@@ -244,16 +447,18 @@ impl<A: InputAction> ButtonMapping<A> {
         mouse_buttons: Option<&ButtonInput<MouseButton>>,
         gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
     ) -> bool {
-        self.get_buttons(action)
-            .map(|bts| {
-                bts.iter()
-                    .any(|b| b.just_pressed(key_codes, mouse_buttons, gamepad_buttons))
-            })
-            .unwrap_or_default()
+        self.evaluate(
+            action,
+            None,
+            |b| b.just_pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.just_pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.pressed(key_codes, mouse_buttons, gamepad_buttons),
+        )
     }
 
     /// Check if the `action` was released in this cycle.
     /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Touch buttons are ignored, use `ActionState` for touch input.
     pub fn just_released(
         &self,
         action: &A,
@@ -261,12 +466,185 @@ impl<A: InputAction> ButtonMapping<A> {
         mouse_buttons: Option<&ButtonInput<MouseButton>>,
         gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
     ) -> bool {
-        self.get_buttons(action)
-            .map(|bts| {
-                bts.iter()
-                    .any(|b| b.just_released(key_codes, mouse_buttons, gamepad_buttons))
+        self.evaluate(
+            action,
+            None,
+            |b| b.just_released(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.just_released(key_codes, mouse_buttons, gamepad_buttons),
+            |c| {
+                c.just_released(key_codes, mouse_buttons, gamepad_buttons)
+                    || c.pressed(key_codes, mouse_buttons, gamepad_buttons)
+            },
+        )
+    }
+
+    /// Check if the `action` is currently pressed, respecting the active `contexts`.
+    /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Touch buttons are ignored, use `ActionState` for touch input.
+    pub fn pressed_in(
+        &self,
+        action: &A,
+        contexts: &InputContexts<A>,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> bool {
+        self.evaluate(
+            action,
+            Some(contexts),
+            |b| b.pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.pressed(key_codes, mouse_buttons, gamepad_buttons),
+        )
+    }
+
+    /// Check if the `action` was pressed in this cycle, respecting the active `contexts`.
+    /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Touch buttons are ignored, use `ActionState` for touch input.
+    pub fn just_pressed_in(
+        &self,
+        action: &A,
+        contexts: &InputContexts<A>,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> bool {
+        self.evaluate(
+            action,
+            Some(contexts),
+            |b| b.just_pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.just_pressed(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.pressed(key_codes, mouse_buttons, gamepad_buttons),
+        )
+    }
+
+    /// Check if the `action` was released in this cycle, respecting the active `contexts`.
+    /// If an optional ButtonInput is omitted it returns false for that button.
+    /// Touch buttons are ignored, use `ActionState` for touch input.
+    pub fn just_released_in(
+        &self,
+        action: &A,
+        contexts: &InputContexts<A>,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> bool {
+        self.evaluate(
+            action,
+            Some(contexts),
+            |b| b.just_released(key_codes, mouse_buttons, gamepad_buttons),
+            |c| c.just_released(key_codes, mouse_buttons, gamepad_buttons),
+            |c| {
+                c.just_released(key_codes, mouse_buttons, gamepad_buttons)
+                    || c.pressed(key_codes, mouse_buttons, gamepad_buttons)
+            },
+        )
+    }
+
+    /// Check if a touch button of `action` is currently pressed, respecting the active `contexts`.
+    pub fn touch_pressed_in(
+        &self,
+        action: &A,
+        contexts: &InputContexts<A>,
+        touch: Option<&ButtonInput<TouchButton>>,
+    ) -> bool {
+        self.evaluate(
+            action,
+            Some(contexts),
+            |b| b.touch_pressed(touch),
+            |_| false,
+            |_| false,
+        )
+    }
+
+    /// Evaluate the plain buttons and chords of `action` with `check` and `chord_check`.
+    /// Bindings that are part of a larger chord for which `suppress` holds are skipped, so Ctrl+S does not also trigger S.
+    /// Without `contexts` the context of the mapping is ignored.
+    fn evaluate(
+        &self,
+        action: &A,
+        contexts: Option<&InputContexts<A>>,
+        check: impl Fn(&GenericButton) -> bool,
+        chord_check: impl Fn(&ButtonChord) -> bool,
+        suppress: impl Fn(&ButtonChord) -> bool,
+    ) -> bool {
+        let Some(mapping) = self.get_from_action(action) else {
+            return false;
+        };
+        if let (Some(context), Some(contexts)) = (&mapping.context, contexts) {
+            if !contexts.is_active(context) {
+                return false;
+            }
+        }
+        mapping
+            .buttons
+            .iter()
+            .filter(|b| {
+                contexts.is_none_or(|c| self.button_visible(b, mapping.context.as_deref(), c))
+            })
+            .any(|b| check(b) && !self.suppressed(std::slice::from_ref(b), contexts, &suppress))
+            || mapping
+                .chords
+                .iter()
+                .any(|c| chord_check(c) && !self.suppressed(c.get_buttons(), contexts, &suppress))
+    }
+
+    /// Check if a chord with more buttons that contains all `buttons` satisfies `suppress`.
+    /// With `contexts` only chords of mappings whose context is active and that see all their buttons count.
+    fn suppressed(
+        &self,
+        buttons: &[GenericButton],
+        contexts: Option<&InputContexts<A>>,
+        suppress: &impl Fn(&ButtonChord) -> bool,
+    ) -> bool {
+        self.mapped_buttons
+            .iter()
+            .filter(|m| {
+                contexts
+                    .is_none_or(|contexts| m.context.as_ref().is_none_or(|c| contexts.is_active(c)))
+            })
+            .flat_map(|m| m.chords.iter().map(move |c| (m, c)))
+            .any(|(m, c)| {
+                c.get_buttons().len() > buttons.len()
+                    && c.contains_all(buttons)
+                    && contexts.is_none_or(|contexts| {
+                        c.get_buttons()
+                            .iter()
+                            .all(|b| self.button_visible(b, m.context.as_deref(), contexts))
+                    })
+                    && suppress(c)
             })
-            .unwrap_or_default()
+    }
+
+    /// Check if `button` reaches mappings in `context` or if a consuming context above takes it.
+    fn button_visible(
+        &self,
+        button: &GenericButton,
+        context: Option<&str>,
+        contexts: &InputContexts<A>,
+    ) -> bool {
+        for layer in contexts.ordered() {
+            if context == Some(layer.name.as_str()) {
+                return true;
+            }
+            if layer.consume
+                && self
+                    .get_from_button(button)
+                    .any(|m| m.context.as_deref() == Some(layer.name.as_str()))
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// All actions that have buttons or axes mapped.
+    pub fn actions(&self) -> impl Iterator<Item = A> + '_ {
+        self.mapped_buttons
+            .iter()
+            .map(|m| m.action)
+            .chain(self.mapped_axes.iter().map(|m| m.action))
+            .chain(self.mapped_dual_axes.iter().map(|m| m.action))
     }
 
     /// Get the `&MappedButtons<A>` entry for `action` if it exists.
@@ -276,16 +654,25 @@ impl<A: InputAction> ButtonMapping<A> {
             .and_then(|i| self.mapped_buttons.get(*i))
     }
 
-    /// Get the `&MappedButtons<A>` entry for `button` if it exists.
-    /// TODO: Allow buttons to be mapped to multiple actions.
-    pub fn get_from_button(&self, button: &GenericButton) -> Option<&MappedButtons<A>> {
+    /// Get all `&MappedButtons<A>` entries that map `button`.
+    pub fn get_from_button<'a>(
+        &'a self,
+        button: &GenericButton,
+    ) -> impl Iterator<Item = &'a MappedButtons<A>> + 'a {
         self.from_button_map
             .get(button)
-            .and_then(|i| self.mapped_buttons.get(*i))
+            .into_iter()
+            .flatten()
+            .filter_map(|i| self.mapped_buttons.get(*i))
     }
 
-    /// Get the `Action` that the `button` is mapped to.
+    /// Get the first `Action` that the `button` is mapped to.
     pub fn get_action(&self, button: &GenericButton) -> Option<A> {
+        self.get_from_button(button).next().map(|m| m.action)
+    }
+
+    /// Get all `Action`s that the `button` is mapped to.
+    pub fn get_actions<'a>(&'a self, button: &GenericButton) -> impl Iterator<Item = A> + 'a {
         self.get_from_button(button).map(|m| m.action)
     }
 
@@ -308,31 +695,285 @@ impl<A: InputAction> ButtonMapping<A> {
         {
             let i = self.from_action_map.get(&action).unwrap(); // TODO: this is ugly
             mapping.buttons.iter().for_each(|b| {
-                let _ = self.from_button_map.remove(b);
+                if let Some(indices) = self.from_button_map.get_mut(b) {
+                    indices.retain(|j| j != i);
+                    if indices.is_empty() {
+                        self.from_button_map.remove(b);
+                    }
+                }
             });
             buttons.iter().for_each(|b| {
-                let _ = self.from_button_map.insert(*b, *i);
+                self.from_button_map.entry(*b).or_default().push(*i);
             });
             self.mapped_buttons.get_mut(*i).unwrap().buttons = buttons; // TODO: Also kinda ugly ngl
         }
     }
 
+    /// Actions other than `action` that use `binding` in the same context as `action`.
+    pub fn conflicts(&self, action: &A, binding: &Binding) -> Vec<A> {
+        let context = self.get_from_action(action).and_then(|m| m.get_context());
+        self.mapped_buttons
+            .iter()
+            .filter(|m| m.action != *action && m.get_context() == context)
+            .filter(|m| m.has_binding(binding))
+            .map(|m| m.action)
+            .collect()
+    }
+
+    /// Check `rebind` for conflicts. Returns the rebind if it can be applied directly.
+    pub fn check_rebind(&self, rebind: Rebind<A>) -> Result<Rebind<A>, RebindConflict<A>> {
+        let conflicting = self.conflicts(&rebind.action, &rebind.binding);
+        if conflicting.is_empty() {
+            Ok(rebind)
+        } else {
+            Err(RebindConflict {
+                rebind: Box::new(rebind),
+                conflicting,
+            })
+        }
+    }
+
+    /// Apply `rebind` without checking for conflicts.
+    pub fn apply_rebind(&mut self, rebind: &Rebind<A>) {
+        if let Some(replacing) = &rebind.replacing {
+            self.remove_binding(&rebind.action, replacing);
+        }
+        self.add_binding(rebind.action, rebind.binding.clone());
+    }
+
+    /// Resolve `conflict` with `resolution`. Returns true if the rebind was applied.
+    pub fn resolve_conflict(
+        &mut self,
+        conflict: RebindConflict<A>,
+        resolution: ConflictResolution,
+    ) -> bool {
+        let rebind = conflict.rebind;
+        match resolution {
+            ConflictResolution::Cancel => return false,
+            ConflictResolution::Replace => {
+                for action in conflict.conflicting.iter() {
+                    self.remove_binding(action, &rebind.binding);
+                }
+            }
+            ConflictResolution::Swap => {
+                for action in conflict.conflicting.iter() {
+                    self.remove_binding(action, &rebind.binding);
+                    if let Some(replacing) = &rebind.replacing {
+                        self.add_binding(*action, replacing.clone());
+                    }
+                }
+            }
+        }
+        self.apply_rebind(&rebind);
+        true
+    }
+
+    /// Add `binding` to `action`, creating a mapping if the action has none.
+    pub fn add_binding(&mut self, action: A, binding: Binding) {
+        let Some(i) = self.from_action_map.get(&action).copied() else {
+            self.insert_mapping(MappedButtons::new(action, &[]).with_binding(binding));
+            return;
+        };
+        let mapping = &mut self.mapped_buttons[i];
+        if mapping.has_binding(&binding) {
+            return;
+        }
+        match binding {
+            Binding::Button(b) => {
+                mapping.buttons.push(b);
+                self.from_button_map.entry(b).or_default().push(i);
+            }
+            Binding::Chord(c) => mapping.chords.push(c),
+        }
+    }
+
+    /// Remove `binding` from `action`. Returns false if the action did not have it.
+    pub fn remove_binding(&mut self, action: &A, binding: &Binding) -> bool {
+        let Some(i) = self.from_action_map.get(action).copied() else {
+            return false;
+        };
+        let mapping = &mut self.mapped_buttons[i];
+        match binding {
+            Binding::Button(b) => {
+                let len = mapping.buttons.len();
+                mapping.buttons.retain(|x| x != b);
+                if len == mapping.buttons.len() {
+                    return false;
+                }
+                if let Some(indices) = self.from_button_map.get_mut(b) {
+                    indices.retain(|j| *j != i);
+                    if indices.is_empty() {
+                        self.from_button_map.remove(b);
+                    }
+                }
+                true
+            }
+            Binding::Chord(c) => {
+                let len = mapping.chords.len();
+                mapping.chords.retain(|x| !x.same_buttons(c));
+                len != mapping.chords.len()
+            }
+        }
+    }
+
     /// Inserts a new mapping and adds the action `A` and the Buttons to internal maps.
+    /// Buttons may be shared with other actions, but every action can only be inserted once.
     pub fn insert_mapping(&mut self, mapping: MappedButtons<A>) -> bool {
-        if self.from_action_map.contains_key(&mapping.action)
-            || mapping
-                .buttons
-                .iter()
-                .any(|b| self.from_button_map.contains_key(b))
-        {
+        if self.from_action_map.contains_key(&mapping.action) {
             return false; // TODO: What do if this happens?
         }
         mapping.buttons.iter().for_each(|b| {
-            let _ = self.from_button_map.insert(*b, self.mapped_buttons.len());
+            self.from_button_map
+                .entry(*b)
+                .or_default()
+                .push(self.mapped_buttons.len());
         });
         self.from_action_map
             .insert(mapping.action, self.mapped_buttons.len());
         self.mapped_buttons.push(mapping);
         true
     }
+
+    /// Copy of this mapping with all device independent gamepad bindings resolved to `gamepad`.
+    /// Bindings to other gamepads are removed.
+    pub fn for_gamepad(&self, gamepad: Gamepad) -> Self {
+        let other = |b: &GenericButton| b.gamepad().is_some_and(|g| g != gamepad);
+        let other_axis = |a: &AxisBinding| a.axis.gamepad().is_some_and(|g| g != gamepad);
+        let mut mapping = MappingData::from(self.clone());
+        mapping.mapped_buttons.iter_mut().for_each(|m| {
+            m.buttons.retain(|b| !other(b));
+            m.chords.retain(|c| !c.get_buttons().iter().any(other));
+            m.sequences.retain(|s| !s.get_buttons().iter().any(other));
+            m.buttons.iter_mut().for_each(|b| *b = b.for_gamepad(gamepad));
+            m.chords = m.chords.drain(..).map(|c| c.for_gamepad(gamepad)).collect();
+            m.sequences = m.sequences.drain(..).map(|s| s.for_gamepad(gamepad)).collect();
+        });
+        mapping.mapped_axes.iter_mut().for_each(|m| {
+            m.axes.retain(|a| !other_axis(a));
+            m.axes.iter_mut().for_each(|a| *a = a.for_gamepad(gamepad));
+        });
+        mapping.mapped_dual_axes.iter_mut().for_each(|m| {
+            m.axes.retain(|a| !other_axis(&a.x) && !other_axis(&a.y));
+            m.axes.iter_mut().for_each(|a| *a = a.for_gamepad(gamepad));
+        });
+        mapping.into()
+    }
+
+    /// Inserts a mapping, replacing the existing mapping of the action.
+    pub fn set_mapping(&mut self, mapping: MappedButtons<A>) {
+        match self.from_action_map.get(&mapping.action) {
+            Some(i) => {
+                let existing = &mut self.mapped_buttons[*i];
+                existing.chords = mapping.chords;
+                existing.sequences = mapping.sequences;
+                existing.context = mapping.context;
+                existing.interactions = mapping.interactions;
+                existing.buffer = mapping.buffer;
+                self.update_buttons(mapping.action, mapping.buttons);
+            }
+            None => {
+                self.insert_mapping(mapping);
+            }
+        }
+    }
+
+    /// Value of the `action` on its mapped axes. If several axes are mapped, the one with the largest magnitude wins.
+    /// If an optional input is omitted it returns 0. for that axis.
+    pub fn axis_value(
+        &self,
+        action: &A,
+        gamepad_axes: Option<&Axis<GamepadAxis>>,
+        gamepad_buttons: Option<&Axis<GamepadButton>>,
+        mouse_axes: Option<&MouseAxisInput>,
+    ) -> f32 {
+        self.get_axes(action)
+            .map(|axes| {
+                axes.iter()
+                    .map(|a| a.value(gamepad_axes, gamepad_buttons, mouse_axes))
+                    .fold(0., |acc: f32, v| if v.abs() > acc.abs() { v } else { acc })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Value of the `action` on its mapped dual axes. If several are mapped, the one with the largest length wins.
+    /// If an optional input is omitted it returns 0. for that axis.
+    pub fn dual_axis_value(
+        &self,
+        action: &A,
+        gamepad_axes: Option<&Axis<GamepadAxis>>,
+        gamepad_buttons: Option<&Axis<GamepadButton>>,
+        mouse_axes: Option<&MouseAxisInput>,
+    ) -> Vec2 {
+        self.get_dual_axes(action)
+            .map(|axes| {
+                axes.iter()
+                    .map(|a| a.value(gamepad_axes, gamepad_buttons, mouse_axes))
+                    .fold(Vec2::ZERO, |acc, v| {
+                        if v.length_squared() > acc.length_squared() {
+                            v
+                        } else {
+                            acc
+                        }
+                    })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the axes that the `action` is mapped to.
+    pub fn get_axes(&self, action: &A) -> Option<&[AxisBinding]> {
+        self.from_action_axis_map
+            .get(action)
+            .and_then(|i| self.mapped_axes.get(*i))
+            .map(|m| m.axes.as_slice())
+    }
+
+    /// Get the dual axes that the `action` is mapped to.
+    pub fn get_dual_axes(&self, action: &A) -> Option<&[DualAxisBinding]> {
+        self.from_action_dual_axis_map
+            .get(action)
+            .and_then(|i| self.mapped_dual_axes.get(*i))
+            .map(|m| m.axes.as_slice())
+    }
+
+    /// Inserts a new axis mapping. Returns false if the action already has one.
+    pub fn insert_axis_mapping(&mut self, mapping: MappedAxes<A>) -> bool {
+        if self.from_action_axis_map.contains_key(&mapping.action) {
+            return false;
+        }
+        self.from_action_axis_map
+            .insert(mapping.action, self.mapped_axes.len());
+        self.mapped_axes.push(mapping);
+        true
+    }
+
+    /// Inserts a new dual axis mapping. Returns false if the action already has one.
+    pub fn insert_dual_axis_mapping(&mut self, mapping: MappedDualAxes<A>) -> bool {
+        if self.from_action_dual_axis_map.contains_key(&mapping.action) {
+            return false;
+        }
+        self.from_action_dual_axis_map
+            .insert(mapping.action, self.mapped_dual_axes.len());
+        self.mapped_dual_axes.push(mapping);
+        true
+    }
+
+    /// Inserts an axis mapping, replacing the existing axis mapping of the action.
+    pub fn set_axis_mapping(&mut self, mapping: MappedAxes<A>) {
+        match self.from_action_axis_map.get(&mapping.action) {
+            Some(i) => self.mapped_axes[*i] = mapping,
+            None => {
+                self.insert_axis_mapping(mapping);
+            }
+        }
+    }
+
+    /// Inserts a dual axis mapping, replacing the existing dual axis mapping of the action.
+    pub fn set_dual_axis_mapping(&mut self, mapping: MappedDualAxes<A>) {
+        match self.from_action_dual_axis_map.get(&mapping.action) {
+            Some(i) => self.mapped_dual_axes[*i] = mapping,
+            None => {
+                self.insert_dual_axis_mapping(mapping);
+            }
+        }
+    }
 }
@@ -0,0 +1,246 @@
+//! Saving and loading bindings
+//!
+//! Bindings are stored per action in a versioned RON file. Loading starts from `InputAction::default_mapping()`
+//! and replaces the bindings of every action in the file, so actions added later keep their defaults.
+//! Actions that cannot be parsed are reported in `BindingsReport` and skipped.
+//!
+//! # Example usage:
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use bevy_tarot_chariot::{ButtonMapping, DualAxisBinding, InputAction, MappedButtons, MappedDualAxes};
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize, Deserialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//!     Dash,
+//!     Move,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Jump, KeyCode::Space.into()));
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Dash, KeyCode::ShiftLeft.into()));
+//!         button_mapping.insert_dual_axis_mapping(MappedDualAxes::new_single(SimpleInputAction::Move, DualAxisBinding::any_left_stick()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let file = r#"(
+//!         version: 1,
+//!         actions: {
+//!             "Jump": (buttons: [KeyBoard(KeyW)]),
+//!             "Fly": (buttons: [KeyBoard(KeyF)]),
+//!         },
+//!     )"#;
+//!     let (mapping, report) = ButtonMapping::<SimpleInputAction>::from_ron_str(file).unwrap();
+//!     assert_eq!(report.unknown_actions, vec!["Fly".to_string()]);
+//!     assert_eq!(mapping.get_buttons(&SimpleInputAction::Jump), Some(&[KeyCode::KeyW.into()][..]));
+//!     assert_eq!(mapping.get_buttons(&SimpleInputAction::Dash), Some(&[KeyCode::ShiftLeft.into()][..]));
+//!     let saved = mapping.to_ron_string().unwrap();
+//!     let (loaded, report) = ButtonMapping::<SimpleInputAction>::from_ron_str(&saved).unwrap();
+//!     assert!(report.unknown_actions.is_empty());
+//!     assert_eq!(loaded.get_buttons(&SimpleInputAction::Jump), Some(&[KeyCode::KeyW.into()][..]));
+//!     // Saving and loading the defaults keeps every action once.
+//!     let defaults = SimpleInputAction::default_mapping();
+//!     let (loaded, _) = ButtonMapping::<SimpleInputAction>::from_ron_str(&defaults.to_ron_string().unwrap()).unwrap();
+//!     assert_eq!(loaded.actions().collect::<Vec<_>>(), defaults.actions().collect::<Vec<_>>());
+//! }
+//! ```
+
+use crate::{
+    AxisBinding, ButtonChord, ButtonMapping, ButtonSequence, ChariotError, DualAxisBinding,
+    GenericButton, InputAction, Interactions, MappedAxes, MappedButtons, MappedDualAxes,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Current version of `BindingsFile`.
+pub const BINDINGS_VERSION: u32 = 1;
+
+/// Serialized form of `ButtonMapping`. The lookup maps are rebuilt on deserialization.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(deserialize = "A: Deserialize<'de>"))]
+pub(crate) struct MappingData<A: InputAction> {
+    /// Store MappedButtons
+    #[serde(default)]
+    pub(crate) mapped_buttons: Vec<MappedButtons<A>>,
+    /// Store MappedAxes
+    #[serde(default)]
+    pub(crate) mapped_axes: Vec<MappedAxes<A>>,
+    /// Store MappedDualAxes
+    #[serde(default)]
+    pub(crate) mapped_dual_axes: Vec<MappedDualAxes<A>>,
+}
+
+impl<A: InputAction> From<MappingData<A>> for ButtonMapping<A> {
+    fn from(value: MappingData<A>) -> Self {
+        let mut mapping = ButtonMapping::default();
+        value.mapped_buttons.into_iter().for_each(|m| {
+            mapping.set_mapping(m);
+        });
+        value.mapped_axes.into_iter().for_each(|m| {
+            mapping.set_axis_mapping(m);
+        });
+        value.mapped_dual_axes.into_iter().for_each(|m| {
+            mapping.set_dual_axis_mapping(m);
+        });
+        mapping
+    }
+}
+
+impl<A: InputAction> From<ButtonMapping<A>> for MappingData<A> {
+    fn from(value: ButtonMapping<A>) -> Self {
+        Self {
+            mapped_buttons: value.mapped_buttons,
+            mapped_axes: value.mapped_axes,
+            mapped_dual_axes: value.mapped_dual_axes,
+        }
+    }
+}
+
+/// All bindings of a single action.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ActionBindings {
+    /// Buttons
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buttons: Vec<GenericButton>,
+    /// Chords
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chords: Vec<ButtonChord>,
+    /// Sequences
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sequences: Vec<ButtonSequence>,
+    /// Name of the `InputContext`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Axes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<AxisBinding>,
+    /// Dual axes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dual_axes: Vec<DualAxisBinding>,
+    /// Interaction thresholds, `None` uses the defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interactions: Option<Interactions>,
+    /// Buffer window of presses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer: Option<Duration>,
+}
+
+/// Versioned on disk format for bindings. Actions are stored by their RON representation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BindingsFile {
+    /// Format version, see `BINDINGS_VERSION`.
+    pub version: u32,
+    /// Bindings per action.
+    #[serde(default)]
+    pub actions: BTreeMap<String, ActionBindings>,
+}
+
+/// Notes from loading a `BindingsFile`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BindingsReport {
+    /// Version of the loaded file.
+    pub version: u32,
+    /// Actions in the file that do not exist (anymore).
+    pub unknown_actions: Vec<String>,
+}
+
+impl<A: InputAction> ButtonMapping<A> {
+    /// Compact, versioned representation of all bindings.
+    pub fn to_bindings_file(&self) -> Result<BindingsFile, ChariotError> {
+        let mut actions = BTreeMap::<String, ActionBindings>::new();
+        for action in self.actions() {
+            let key = ron::to_string(&action)?;
+            if actions.contains_key(&key) {
+                continue;
+            }
+            let mut bindings = ActionBindings::default();
+            if let Some(m) = self.get_from_action(&action) {
+                bindings.buttons = m.get_buttons().to_vec();
+                bindings.chords = m.get_chords().to_vec();
+                bindings.sequences = m.get_sequences().to_vec();
+                bindings.context = m.get_context().map(String::from);
+                bindings.interactions =
+                    Some(*m.get_interactions()).filter(|i| *i != Interactions::default());
+                bindings.buffer = m.get_buffer();
+            }
+            bindings.axes = self.get_axes(&action).unwrap_or_default().to_vec();
+            bindings.dual_axes = self.get_dual_axes(&action).unwrap_or_default().to_vec();
+            actions.insert(key, bindings);
+        }
+        Ok(BindingsFile {
+            version: BINDINGS_VERSION,
+            actions,
+        })
+    }
+
+    /// Serialize all bindings as a `BindingsFile` in RON.
+    pub fn to_ron_string(&self) -> Result<String, ChariotError> {
+        Ok(ron::ser::to_string_pretty(
+            &self.to_bindings_file()?,
+            Default::default(),
+        )?)
+    }
+
+    /// Write all bindings as a `BindingsFile` to `path`.
+    pub fn save_bindings<P: AsRef<Path>>(&self, path: P) -> Result<(), ChariotError> {
+        std::fs::write(path, self.to_ron_string()?)?;
+        Ok(())
+    }
+}
+
+impl<A: InputAction + DeserializeOwned> ButtonMapping<A> {
+    /// `A::default_mapping()` with every action in `file` replaced by the bindings from the file.
+    pub fn from_bindings_file(file: BindingsFile) -> Result<(Self, BindingsReport), ChariotError> {
+        if file.version > BINDINGS_VERSION {
+            return Err(ChariotError::UnsupportedVersion(file.version));
+        }
+        let mut mapping = A::default_mapping();
+        let mut report = BindingsReport {
+            version: file.version,
+            unknown_actions: vec![],
+        };
+        for (key, bindings) in file.actions {
+            let Ok(action) = ron::from_str::<A>(&key) else {
+                report.unknown_actions.push(key);
+                continue;
+            };
+            let mut buttons = MappedButtons::new(action, &bindings.buttons)
+                .with_interactions(bindings.interactions.unwrap_or_default());
+            buttons.chords = bindings.chords.into();
+            buttons.sequences = bindings.sequences.into();
+            buttons.context = bindings.context;
+            buttons.buffer = bindings.buffer;
+            // Only replace existing or add non-empty bindings, so no empty duplicates are added.
+            let has_buttons = !(buttons.buttons.is_empty()
+                && buttons.chords.is_empty()
+                && buttons.sequences.is_empty());
+            if has_buttons || mapping.get_from_action(&action).is_some() {
+                mapping.set_mapping(buttons);
+            }
+            if !bindings.axes.is_empty() || mapping.get_axes(&action).is_some() {
+                mapping.set_axis_mapping(MappedAxes::new(action, &bindings.axes));
+            }
+            if !bindings.dual_axes.is_empty() || mapping.get_dual_axes(&action).is_some() {
+                mapping.set_dual_axis_mapping(MappedDualAxes::new(action, &bindings.dual_axes));
+            }
+        }
+        Ok((mapping, report))
+    }
+
+    /// Parse a `BindingsFile` from RON and merge it with `A::default_mapping()`.
+    pub fn from_ron_str(s: &str) -> Result<(Self, BindingsReport), ChariotError> {
+        Self::from_bindings_file(ron::from_str(s)?)
+    }
+
+    /// Load a `BindingsFile` from `path` and merge it with `A::default_mapping()`.
+    pub fn load_bindings<P: AsRef<Path>>(path: P) -> Result<(Self, BindingsReport), ChariotError> {
+        Self::from_ron_str(&std::fs::read_to_string(path)?)
+    }
+}
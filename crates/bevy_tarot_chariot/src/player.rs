@@ -0,0 +1,279 @@
+//! Player slots for local multiplayer
+//!
+//! Bindings with `GenericButton::AnyGamepad` or `GenericAxis::AnyGamepad` are independent of the device.
+//! `PlayerSlots` assigns connected gamepads to players, and `player_plugin::<A>` evaluates the mapping
+//! once per player against that player's gamepad into `PlayerActionStates<A>`.
+//! Bindings to a specific gamepad only apply to the player that gamepad is assigned to.
+//! A gamepad that reconnects gets its old slot back if it is still free. Touch input belongs to the keyboard player.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_app::prelude::*;
+//! use bevy_time::Time;
+//! use bevy_tarot_chariot::{ButtonMapping, InputAction, MappedButtons, PlayerActionStates, PlayerSlots};
+//! use bevy_tarot_chariot::bevy_input::{prelude::*, InputPlugin};
+//! use bevy_tarot_chariot::bevy_input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo};
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//!     Pause,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Jump, GamepadButtonType::South.into()));
+//!         let start = GamepadButton::new(Gamepad::new(3), GamepadButtonType::Start);
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Pause, start.into()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mut app = App::new();
+//!     app.add_plugins(InputPlugin);
+//!     app.init_resource::<Time>();
+//!     app.add_plugins(bevy_tarot_chariot::plugin::<SimpleInputAction>);
+//!     app.add_plugins(bevy_tarot_chariot::player_plugin::<SimpleInputAction>);
+//!     for id in [3, 7] {
+//!         let info = GamepadInfo { name: "Pad".to_string() };
+//!         app.world_mut().send_event(GamepadConnectionEvent::new(Gamepad::new(id), GamepadConnection::Connected(info)));
+//!     }
+//!     app.update();
+//!     assert_eq!(app.world().resource::<PlayerSlots>().gamepad(1), Some(Gamepad::new(7)));
+//!     app.world_mut()
+//!         .resource_mut::<ButtonInput<GamepadButton>>()
+//!         .press(GamepadButton::new(Gamepad::new(7), GamepadButtonType::South));
+//!     app.update();
+//!     let players = app.world().resource::<PlayerActionStates<SimpleInputAction>>();
+//!     assert!(!players.get(0).unwrap().pressed(&SimpleInputAction::Jump));
+//!     assert!(players.get(1).unwrap().pressed(&SimpleInputAction::Jump));
+//!     app.world_mut()
+//!         .resource_mut::<ButtonInput<GamepadButton>>()
+//!         .press(GamepadButton::new(Gamepad::new(3), GamepadButtonType::Start));
+//!     app.update();
+//!     let players = app.world().resource::<PlayerActionStates<SimpleInputAction>>();
+//!     assert!(players.get(0).unwrap().pressed(&SimpleInputAction::Pause));
+//!     assert!(!players.get(1).unwrap().pressed(&SimpleInputAction::Pause));
+//! }
+//! ```
+
+use crate::{
+    ActionState, ActionStateSystem, ButtonMapping, DeviceInput, InputAction, InputContexts,
+    InputDevices,
+};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy_input::prelude::*;
+use bevy_input::InputSystem;
+use bevy_time::Time;
+use std::collections::HashMap;
+
+/// Evaluates `ButtonMapping<A>` per player into `PlayerActionStates<A>`.
+/// Inserts `PlayerSlots` with 4 slots if it does not exist yet.
+pub fn player_plugin<A: InputAction>(app: &mut App) {
+    if !app.world().contains_resource::<PlayerSlots>() {
+        app.insert_resource(PlayerSlots::new(4));
+        app.add_systems(PreUpdate, assign_gamepads.after(InputSystem));
+    }
+    if !app.world().contains_resource::<ButtonMapping<A>>() {
+        app.insert_resource(A::default_mapping());
+    }
+    app.init_resource::<InputContexts<A>>();
+    app.init_resource::<PlayerActionStates<A>>();
+    app.add_systems(
+        PreUpdate,
+        update_player_action_states::<A>
+            .in_set(ActionStateSystem)
+            .after(InputSystem)
+            .after(assign_gamepads),
+    );
+}
+
+/// Gamepads assigned to players.
+#[derive(Resource, Clone, Debug)]
+pub struct PlayerSlots {
+    /// Gamepad per player.
+    slots: Vec<Option<Gamepad>>,
+    /// Last slot of every gamepad that was assigned.
+    last_slot: HashMap<Gamepad, usize>,
+    /// Player that uses keyboard and mouse.
+    keyboard_player: Option<usize>,
+}
+
+impl PlayerSlots {
+    /// `count` empty slots. Player 0 uses keyboard and mouse.
+    pub fn new(count: usize) -> Self {
+        Self {
+            slots: vec![None; count],
+            last_slot: HashMap::new(),
+            keyboard_player: Some(0),
+        }
+    }
+
+    /// Set the player that uses keyboard and mouse. `None` ignores keyboard and mouse.
+    pub fn with_keyboard_player(mut self, player: Option<usize>) -> Self {
+        self.keyboard_player = player;
+        self
+    }
+
+    /// Number of slots.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Check if there are no slots.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Gamepad of `player`.
+    pub fn gamepad(&self, player: usize) -> Option<Gamepad> {
+        self.slots.get(player).copied().flatten()
+    }
+
+    /// Player that `gamepad` is assigned to.
+    pub fn player(&self, gamepad: Gamepad) -> Option<usize> {
+        self.slots.iter().position(|g| *g == Some(gamepad))
+    }
+
+    /// Player that uses keyboard and mouse.
+    pub fn keyboard_player(&self) -> Option<usize> {
+        self.keyboard_player
+    }
+
+    /// Assign `gamepad` to `player`, removing it from its previous slot. Returns false if there is no such slot.
+    pub fn assign(&mut self, player: usize, gamepad: Gamepad) -> bool {
+        if player >= self.slots.len() {
+            return false;
+        }
+        self.unassign_gamepad(gamepad);
+        self.slots[player] = Some(gamepad);
+        self.last_slot.insert(gamepad, player);
+        true
+    }
+
+    /// Remove the gamepad of `player`.
+    pub fn unassign(&mut self, player: usize) -> Option<Gamepad> {
+        self.slots.get_mut(player).and_then(|g| g.take())
+    }
+
+    /// Remove `gamepad` from its slot.
+    pub fn unassign_gamepad(&mut self, gamepad: Gamepad) -> Option<usize> {
+        let player = self.player(gamepad)?;
+        self.slots[player] = None;
+        Some(player)
+    }
+
+    /// Assign a newly connected `gamepad` to its previous slot if it is free, otherwise to the first free slot.
+    pub fn connect(&mut self, gamepad: Gamepad) -> Option<usize> {
+        if let Some(player) = self.player(gamepad) {
+            return Some(player);
+        }
+        let player = self
+            .last_slot
+            .get(&gamepad)
+            .copied()
+            .filter(|p| self.slots.get(*p).is_some_and(|g| g.is_none()))
+            .or_else(|| self.slots.iter().position(|g| g.is_none()))?;
+        self.assign(player, gamepad);
+        Some(player)
+    }
+
+    /// Free the slot of a disconnected `gamepad`. The slot is remembered for reconnection.
+    pub fn disconnect(&mut self, gamepad: Gamepad) -> Option<usize> {
+        self.unassign_gamepad(gamepad)
+    }
+}
+
+/// Evaluated `ActionState<A>` per player.
+#[derive(Resource, Clone, Debug)]
+pub struct PlayerActionStates<A: InputAction> {
+    /// State per player.
+    states: Vec<ActionState<A>>,
+    /// Mapping resolved to the gamepad of each player.
+    resolved: Vec<Option<ButtonMapping<A>>>,
+}
+
+impl<A: InputAction> Default for PlayerActionStates<A> {
+    fn default() -> Self {
+        Self {
+            states: vec![],
+            resolved: vec![],
+        }
+    }
+}
+
+impl<A: InputAction> PlayerActionStates<A> {
+    /// State of `player`.
+    pub fn get(&self, player: usize) -> Option<&ActionState<A>> {
+        self.states.get(player)
+    }
+
+    /// Mutable state of `player`.
+    pub fn get_mut(&mut self, player: usize) -> Option<&mut ActionState<A>> {
+        self.states.get_mut(player)
+    }
+
+    /// Iterate over the states of all players.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &ActionState<A>)> {
+        self.states.iter().enumerate()
+    }
+}
+
+/// Assigns connecting gamepads to `PlayerSlots` and frees the slots of disconnecting ones.
+pub fn assign_gamepads(
+    mut slots: ResMut<PlayerSlots>,
+    mut connections: EventReader<GamepadConnectionEvent>,
+) {
+    for event in connections.read() {
+        match event.connection {
+            GamepadConnection::Connected(_) => {
+                slots.connect(event.gamepad);
+            }
+            GamepadConnection::Disconnected => {
+                slots.disconnect(event.gamepad);
+            }
+        }
+    }
+}
+
+/// Reads the input of every player into `PlayerActionStates<A>`.
+pub fn update_player_action_states<A: InputAction>(
+    mapping: Res<ButtonMapping<A>>,
+    contexts: Res<InputContexts<A>>,
+    slots: Res<PlayerSlots>,
+    devices: InputDevices,
+    time: Res<Time>,
+    mut players: ResMut<PlayerActionStates<A>>,
+) {
+    let players = players.as_mut();
+    if slots.is_changed() || mapping.is_changed() || players.resolved.len() != slots.len() {
+        players.resolved = (0..slots.len())
+            .map(|p| slots.gamepad(p).map(|g| mapping.for_gamepad(g)))
+            .collect();
+        players.states.resize_with(slots.len(), Default::default);
+    }
+    let input = devices.get();
+    for (player, state) in players.states.iter_mut().enumerate() {
+        let keyboard = slots.keyboard_player() == Some(player);
+        let resolved = players.resolved[player].as_ref();
+        let player_input = DeviceInput {
+            key_codes: input.key_codes.filter(|_| keyboard),
+            mouse_buttons: input.mouse_buttons.filter(|_| keyboard),
+            mouse_axes: input.mouse_axes.filter(|_| keyboard),
+            touch: input.touch.filter(|_| keyboard),
+            gamepad_buttons: input.gamepad_buttons.filter(|_| resolved.is_some()),
+            gamepad_axes: input.gamepad_axes.filter(|_| resolved.is_some()),
+            gamepad_button_axes: input.gamepad_button_axes.filter(|_| resolved.is_some()),
+        };
+        state.update(
+            resolved.unwrap_or(&mapping),
+            &contexts,
+            player_input,
+            time.delta(),
+        );
+    }
+}
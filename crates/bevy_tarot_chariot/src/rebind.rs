@@ -0,0 +1,298 @@
+//! Capturing the next input for rebinding menus
+//!
+//! `RebindSession::listen` starts capturing. `capture_rebind` (added by `plugin::<A>`) stores the next
+//! button or chord that is not blacklisted, already checked against the `ButtonMapping`.
+//! Conflicts are returned as `RebindConflict` and can be resolved with `ButtonMapping::resolve_conflict`.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_tarot_chariot::*;
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//!     Interact,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Jump, KeyCode::Space.into()));
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Interact, KeyCode::KeyE.into()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mut mapping = SimpleInputAction::default_mapping();
+//!     let mut session = RebindSession::default();
+//!     let mut input = ButtonInput::default();
+//!     session.listen_replacing(SimpleInputAction::Jump, GenericButton::from(KeyCode::Space).into());
+//!     input.press(KeyCode::KeyE);
+//!     let binding = session.capture(Some(&input), None, None).unwrap();
+//!     session.finish(binding, &mapping);
+//!     let conflict = session.take_result().unwrap().unwrap_err();
+//!     assert_eq!(conflict.conflicting, vec![SimpleInputAction::Interact]);
+//!     assert!(mapping.resolve_conflict(conflict, ConflictResolution::Swap));
+//!     assert_eq!(mapping.get_buttons(&SimpleInputAction::Jump), Some(&[KeyCode::KeyE.into()][..]));
+//!     assert_eq!(mapping.get_buttons(&SimpleInputAction::Interact), Some(&[KeyCode::Space.into()][..]));
+//!     // Chords conflict regardless of the order and side of their modifiers.
+//!     let save = ButtonChord::new(&[KeyCode::ControlLeft.into(), KeyCode::ShiftLeft.into(), KeyCode::KeyS.into()]);
+//!     let other = ButtonChord::new(&[KeyCode::ShiftRight.into(), KeyCode::ControlLeft.into(), KeyCode::KeyS.into()]);
+//!     assert!(Binding::Chord(save).matches(&Binding::Chord(other)));
+//! }
+//! ```
+
+use crate::{is_modifier, ButtonChord, ButtonMapping, GenericButton, InputAction, InputDevices};
+use bevy_ecs::prelude::*;
+use bevy_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::collections::HashSet;
+
+/// Anything a single action slot can be bound to.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    /// Single button
+    Button(GenericButton),
+    /// Several buttons pressed together
+    Chord(ButtonChord),
+}
+
+impl Binding {
+    /// Check if both bindings press the same buttons. Chords are compared in any order and with
+    /// left and right modifiers treated as equal (see `ButtonChord::same_buttons`).
+    pub fn matches(&self, other: &Binding) -> bool {
+        match (self, other) {
+            (Binding::Button(a), Binding::Button(b)) => a == b,
+            (Binding::Chord(a), Binding::Chord(b)) => a.same_buttons(b),
+            _ => false,
+        }
+    }
+}
+
+impl From<GenericButton> for Binding {
+    fn from(value: GenericButton) -> Self {
+        Self::Button(value)
+    }
+}
+
+impl From<ButtonChord> for Binding {
+    fn from(value: ButtonChord) -> Self {
+        Self::Chord(value)
+    }
+}
+
+/// Request to bind `binding` to `action`, optionally in place of an existing binding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rebind<A: InputAction> {
+    /// Action that gets the new binding.
+    pub action: A,
+    /// Binding of `action` that is replaced. `None` adds the binding.
+    pub replacing: Option<Binding>,
+    /// New binding.
+    pub binding: Binding,
+}
+
+/// `Rebind` whose binding is already used by other actions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebindConflict<A: InputAction> {
+    /// The rebind that caused the conflict.
+    pub rebind: Box<Rebind<A>>,
+    /// Actions that already use the binding (in the same context).
+    pub conflicting: Vec<A>,
+}
+
+/// How to resolve a `RebindConflict`.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictResolution {
+    /// Conflicting actions get the binding that is replaced (or lose the binding if nothing is replaced).
+    Swap,
+    /// Remove the binding from the conflicting actions.
+    Replace,
+    /// Keep everything as it is.
+    Cancel,
+}
+
+/// Listens for the next input to rebind an action of type `A`.
+#[derive(Resource, Clone, Debug)]
+pub struct RebindSession<A: InputAction> {
+    /// Action (and replaced binding) that is currently captured for.
+    target: Option<(A, Option<Binding>)>,
+    /// Buttons that are never captured.
+    blacklist: HashSet<GenericButton>,
+    /// Result of the last capture.
+    result: Option<Result<Rebind<A>, RebindConflict<A>>>,
+}
+
+impl<A: InputAction> Default for RebindSession<A> {
+    fn default() -> Self {
+        Self {
+            target: None,
+            blacklist: HashSet::new(),
+            result: None,
+        }
+    }
+}
+
+impl<A: InputAction> RebindSession<A> {
+    /// Capture the next input as an additional binding for `action`.
+    pub fn listen(&mut self, action: A) {
+        self.target = Some((action, None));
+        self.result = None;
+    }
+
+    /// Capture the next input as a replacement for `replacing` of `action`.
+    pub fn listen_replacing(&mut self, action: A, replacing: Binding) {
+        self.target = Some((action, Some(replacing)));
+        self.result = None;
+    }
+
+    /// Stop capturing without a result.
+    pub fn cancel(&mut self) {
+        self.target = None;
+    }
+
+    /// Check if the session is waiting for input.
+    pub fn is_listening(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Action that is currently captured for.
+    pub fn target(&self) -> Option<&A> {
+        self.target.as_ref().map(|(a, _)| a)
+    }
+
+    /// Never capture `button`.
+    pub fn blacklist(&mut self, button: GenericButton) {
+        self.blacklist.insert(button);
+    }
+
+    /// Capture `button` again.
+    pub fn remove_from_blacklist(&mut self, button: &GenericButton) {
+        self.blacklist.remove(button);
+    }
+
+    /// Check if `button` is blacklisted.
+    pub fn is_blacklisted(&self, button: &GenericButton) -> bool {
+        self.blacklist.contains(button)
+    }
+
+    /// Take the result of the last capture. `Ok` can be applied with `ButtonMapping::apply_rebind`.
+    pub fn take_result(&mut self) -> Option<Result<Rebind<A>, RebindConflict<A>>> {
+        self.result.take()
+    }
+
+    /// Finish the capture with `binding`.
+    pub fn finish(&mut self, binding: Binding, mapping: &ButtonMapping<A>) {
+        if let Some((action, replacing)) = self.target.take() {
+            self.result = Some(mapping.check_rebind(Rebind {
+                action,
+                replacing,
+                binding,
+            }));
+        }
+    }
+
+    /// Find the next binding in the current input. Keys and mouse buttons pressed with modifiers become chords.
+    /// Modifiers on their own are captured when they are released.
+    pub fn capture(
+        &self,
+        key_codes: Option<&ButtonInput<KeyCode>>,
+        mouse_buttons: Option<&ButtonInput<MouseButton>>,
+        gamepad_buttons: Option<&ButtonInput<GamepadButton>>,
+    ) -> Option<Binding> {
+        let allowed = |b: &GenericButton| !self.blacklist.contains(b);
+        let modifiers = key_codes
+            .map(|k| {
+                let mut modifiers = k
+                    .get_pressed()
+                    .copied()
+                    .filter(|k| is_modifier(*k))
+                    .collect::<SmallVec<[KeyCode; 2]>>();
+                modifiers.sort_by_key(|k| modifier_order(*k));
+                modifiers
+                    .into_iter()
+                    .map(GenericButton::from)
+                    .filter(allowed)
+                    .collect::<SmallVec<[GenericButton; 2]>>()
+            })
+            .unwrap_or_default();
+        let with_modifiers = |button: GenericButton| {
+            if modifiers.is_empty() {
+                Binding::Button(button)
+            } else {
+                let mut buttons = modifiers.clone();
+                buttons.push(button);
+                Binding::Chord(ButtonChord::new(&buttons))
+            }
+        };
+        let key = key_codes.and_then(|k| {
+            k.get_just_pressed()
+                .filter(|k| !is_modifier(**k))
+                .map(|k| GenericButton::from(*k))
+                .find(allowed)
+        });
+        let mouse = || {
+            mouse_buttons.and_then(|m| {
+                m.get_just_pressed()
+                    .map(|m| GenericButton::from(*m))
+                    .find(allowed)
+            })
+        };
+        if let Some(button) = key.or_else(mouse) {
+            return Some(with_modifiers(button));
+        }
+        let gamepad = gamepad_buttons.and_then(|g| {
+            g.get_just_pressed()
+                .map(|g| GenericButton::from(*g))
+                .find(allowed)
+        });
+        let modifier = || {
+            key_codes.and_then(|k| {
+                k.get_just_released()
+                    .filter(|k| is_modifier(**k))
+                    .map(|k| GenericButton::from(*k))
+                    .find(allowed)
+            })
+        };
+        gamepad.or_else(modifier).map(Binding::Button)
+    }
+}
+
+/// Order of modifiers in captured chords: Control, Shift, Alt, Super, left before right.
+fn modifier_order(key: KeyCode) -> usize {
+    use KeyCode::*;
+    [
+        ControlLeft,
+        ControlRight,
+        ShiftLeft,
+        ShiftRight,
+        AltLeft,
+        AltRight,
+        SuperLeft,
+        SuperRight,
+    ]
+    .iter()
+    .position(|k| *k == key)
+    .unwrap_or(usize::MAX)
+}
+
+/// Captures the next input for an active `RebindSession<A>`.
+pub fn capture_rebind<A: InputAction>(
+    mapping: Res<ButtonMapping<A>>,
+    devices: InputDevices,
+    mut session: ResMut<RebindSession<A>>,
+) {
+    if !session.is_listening() {
+        return;
+    }
+    if let Some(binding) = session.capture(
+        devices.key_codes.as_deref(),
+        devices.mouse_buttons.as_deref(),
+        devices.gamepad_buttons.as_deref(),
+    ) {
+        session.finish(binding, &mapping);
+    }
+}
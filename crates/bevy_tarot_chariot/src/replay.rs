@@ -0,0 +1,269 @@
+//! Recording and replaying evaluated action states
+//!
+//! `InputRecorder<A>` stores `ActionState<A>` after every update. `InputReplay<A>` writes a recording back
+//! into `ActionState<A>` instead of reading the devices, so gameplay code sees the same input again.
+//! With the `TimePlugin` the replay also sets `TimeUpdateStrategy` to the recorded frame times, so the game
+//! advances exactly as it did while recording.
+//!
+//! # Example usage:
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use bevy_app::prelude::*;
+//! use bevy_time::Time;
+//! use bevy_tarot_chariot::{ActionState, ButtonMapping, InputAction, InputRecorder, InputRecording, InputReplay, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::{prelude::*, InputPlugin};
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize, Deserialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Jump, KeyCode::Space.into()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mut app = App::new();
+//!     app.add_plugins(InputPlugin);
+//!     app.init_resource::<Time>();
+//!     app.add_plugins(bevy_tarot_chariot::plugin::<SimpleInputAction>);
+//!     app.world_mut().resource_mut::<InputRecorder<SimpleInputAction>>().start();
+//!     app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+//!     app.update();
+//!     let recording = app.world_mut().resource_mut::<InputRecorder<SimpleInputAction>>().stop().unwrap();
+//!     let recording = InputRecording::<SimpleInputAction>::from_ron_str(&recording.to_ron_string().unwrap()).unwrap();
+//!
+//!     let mut app = App::new();
+//!     app.add_plugins(InputPlugin);
+//!     app.init_resource::<Time>();
+//!     app.add_plugins(bevy_tarot_chariot::plugin::<SimpleInputAction>);
+//!     app.world_mut().resource_mut::<InputReplay<SimpleInputAction>>().play(recording);
+//!     app.update();
+//!     assert!(app.world().resource::<ActionState<SimpleInputAction>>().just_pressed(&SimpleInputAction::Jump));
+//!     assert!(app.world().resource::<InputReplay<SimpleInputAction>>().is_finished());
+//! }
+//! ```
+
+use crate::{ActionData, ActionState, ChariotError, InputAction};
+use bevy_ecs::prelude::*;
+use bevy_time::{Time, TimeUpdateStrategy};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// Current version of `InputRecording`.
+pub const RECORDING_VERSION: u32 = 1;
+
+/// `ActionState` of a single frame.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "A: Deserialize<'de>"))]
+pub struct RecordedFrame<A: InputAction> {
+    /// Frame time of the recorded frame.
+    pub delta: Duration,
+    /// State of every evaluated action.
+    pub actions: Vec<(A, ActionData)>,
+    /// Buffered presses and their age.
+    #[serde(default)]
+    pub buffered: Vec<(A, Duration)>,
+}
+
+/// Recorded frames of `ActionState<A>`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "A: Deserialize<'de>"))]
+pub struct InputRecording<A: InputAction> {
+    /// Format version, see `RECORDING_VERSION`.
+    pub version: u32,
+    /// Frames in the order they were recorded.
+    pub frames: Vec<RecordedFrame<A>>,
+}
+
+impl<A: InputAction> Default for InputRecording<A> {
+    fn default() -> Self {
+        Self {
+            version: RECORDING_VERSION,
+            frames: vec![],
+        }
+    }
+}
+
+impl<A: InputAction> InputRecording<A> {
+    /// Serialize the recording in RON.
+    pub fn to_ron_string(&self) -> Result<String, ChariotError> {
+        Ok(ron::to_string(self)?)
+    }
+
+    /// Write the recording to `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ChariotError> {
+        std::fs::write(path, self.to_ron_string()?)?;
+        Ok(())
+    }
+}
+
+impl<A: InputAction + DeserializeOwned> InputRecording<A> {
+    /// Parse a recording from RON.
+    pub fn from_ron_str(s: &str) -> Result<Self, ChariotError> {
+        let recording: Self = ron::from_str(s)?;
+        if recording.version > RECORDING_VERSION {
+            return Err(ChariotError::UnsupportedVersion(recording.version));
+        }
+        Ok(recording)
+    }
+
+    /// Load a recording from `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ChariotError> {
+        Self::from_ron_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Records `ActionState<A>` every frame while started.
+#[derive(Resource, Clone, Debug)]
+pub struct InputRecorder<A: InputAction> {
+    /// Recording in progress.
+    recording: Option<InputRecording<A>>,
+}
+
+impl<A: InputAction> Default for InputRecorder<A> {
+    fn default() -> Self {
+        Self { recording: None }
+    }
+}
+
+impl<A: InputAction> InputRecorder<A> {
+    /// Start a new recording, dropping the current one.
+    pub fn start(&mut self) {
+        self.recording = Some(InputRecording::default());
+    }
+
+    /// Stop recording and return the recorded frames.
+    pub fn stop(&mut self) -> Option<InputRecording<A>> {
+        self.recording.take()
+    }
+
+    /// Check if a recording is in progress.
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+/// Replays an `InputRecording<A>` into `ActionState<A>`. Devices are ignored while playing.
+#[derive(Resource, Clone, Debug)]
+pub struct InputReplay<A: InputAction> {
+    /// Recording that is played.
+    recording: Option<InputRecording<A>>,
+    /// Index of the next frame.
+    frame: usize,
+    /// `TimeUpdateStrategy` is set to the recorded frame times.
+    driving_time: bool,
+}
+
+impl<A: InputAction> Default for InputReplay<A> {
+    fn default() -> Self {
+        Self {
+            recording: None,
+            frame: 0,
+            driving_time: false,
+        }
+    }
+}
+
+impl<A: InputAction> InputReplay<A> {
+    /// Start playing `recording` from the first frame.
+    pub fn play(&mut self, recording: InputRecording<A>) {
+        self.recording = Some(recording);
+        self.frame = 0;
+    }
+
+    /// Stop playing and return the recording.
+    pub fn stop(&mut self) -> Option<InputRecording<A>> {
+        self.frame = 0;
+        self.recording.take()
+    }
+
+    /// Check if frames are left to play.
+    pub fn is_playing(&self) -> bool {
+        self.recording
+            .as_ref()
+            .is_some_and(|r| self.frame < r.frames.len())
+    }
+
+    /// Check if a recording was played to the end.
+    pub fn is_finished(&self) -> bool {
+        self.recording
+            .as_ref()
+            .is_some_and(|r| self.frame >= r.frames.len())
+    }
+
+    /// Index of the next frame.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+}
+
+/// Run condition that is true while no `InputReplay<A>` is playing.
+pub fn replay_inactive<A: InputAction>(replay: Option<Res<InputReplay<A>>>) -> bool {
+    replay.is_none_or(|r| !r.is_playing())
+}
+
+/// Appends the current `ActionState<A>` to the running recording.
+pub fn record_action_state<A: InputAction>(
+    state: Res<ActionState<A>>,
+    time: Res<Time>,
+    mut recorder: ResMut<InputRecorder<A>>,
+) {
+    if let Some(recording) = recorder.recording.as_mut() {
+        recording.frames.push(RecordedFrame {
+            delta: time.delta(),
+            actions: state.data.iter().map(|(a, d)| (*a, *d)).collect(),
+            buffered: state.buffered.iter().map(|(a, d)| (*a, *d)).collect(),
+        });
+    }
+}
+
+/// Sets `TimeUpdateStrategy` to the frame time of the next recorded frame while playing
+/// and back to `Automatic` afterwards. Runs before `TimeSystem`.
+pub fn replay_time<A: InputAction>(
+    mut replay: ResMut<InputReplay<A>>,
+    strategy: Option<ResMut<TimeUpdateStrategy>>,
+) {
+    let Some(mut strategy) = strategy else {
+        return;
+    };
+    let delta = replay
+        .recording
+        .as_ref()
+        .and_then(|r| r.frames.get(replay.frame))
+        .map(|f| f.delta);
+    match delta {
+        Some(delta) => {
+            *strategy = TimeUpdateStrategy::ManualDuration(delta);
+            replay.driving_time = true;
+        }
+        None if replay.driving_time => {
+            *strategy = TimeUpdateStrategy::Automatic;
+            replay.driving_time = false;
+        }
+        None => {}
+    }
+}
+
+/// Writes the next recorded frame into `ActionState<A>`.
+pub fn replay_action_state<A: InputAction>(
+    mut replay: ResMut<InputReplay<A>>,
+    mut state: ResMut<ActionState<A>>,
+) {
+    if !replay.is_playing() {
+        return;
+    }
+    let frame = replay.frame;
+    replay.frame += 1;
+    let Some(frame) = replay.recording.as_ref().map(|r| &r.frames[frame]) else {
+        return;
+    };
+    state.data = frame.actions.iter().copied().collect();
+    state.buffered = frame.buffered.iter().copied().collect();
+    state.sequences.clear();
+}
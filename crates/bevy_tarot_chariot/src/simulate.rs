@@ -0,0 +1,269 @@
+//! Headless input simulation for tests
+//!
+//! `InputSimulator<A>` wraps an `App` and presses the bindings of actions by sending the same keyboard, mouse
+//! and gamepad events a real device would. Every call runs whole frames, so systems see the usual
+//! `just_pressed` and `just_released` edges and hold durations grow by a fixed frame time.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_app::prelude::*;
+//! use bevy_ecs::prelude::*;
+//! use bevy_tarot_chariot::{ActionState, ButtonMapping, InputAction, InputSimulator, MappedButtons};
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Jump, KeyCode::Space.into()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! #[derive(Resource, Default)]
+//! struct Jumps(usize);
+//!
+//! fn jump(keys: Res<ButtonInput<KeyCode>>, mut jumps: ResMut<Jumps>) {
+//!     if keys.just_pressed(KeyCode::Space) {
+//!         jumps.0 += 1;
+//!     }
+//! }
+//!
+//! pub fn main() {
+//!     let mut sim = InputSimulator::<SimpleInputAction>::new();
+//!     sim.app_mut().init_resource::<Jumps>().add_systems(Update, jump);
+//!     sim.press(SimpleInputAction::Jump);
+//!     assert!(sim.state().just_pressed(&SimpleInputAction::Jump));
+//!     sim.advance(9);
+//!     assert_eq!(sim.state().held_duration(&SimpleInputAction::Jump), sim.frame_time() * 9);
+//!     sim.release(SimpleInputAction::Jump);
+//!     assert!(sim.state().just_released(&SimpleInputAction::Jump));
+//!     sim.hold_for(SimpleInputAction::Jump, 3);
+//!     assert_eq!(sim.app().world().resource::<Jumps>().0, 2);
+//! }
+//! ```
+
+use crate::{ActionState, ButtonMapping, GenericButton, InputAction};
+use bevy_app::prelude::*;
+use bevy_ecs::entity::Entity;
+use bevy_input::gamepad::GamepadButtonChangedEvent;
+use bevy_input::keyboard::{Key, KeyboardInput, NativeKey};
+use bevy_input::mouse::MouseButtonInput;
+use bevy_input::prelude::*;
+use bevy_input::{ButtonState, InputPlugin};
+use bevy_time::{Time, TimeUpdateStrategy};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Drives an `App` with simulated presses of the bindings of `A`.
+pub struct InputSimulator<A: InputAction> {
+    /// Simulated app
+    app: App,
+    /// Gamepad that presses device independent gamepad buttons.
+    gamepad: Gamepad,
+    /// Time that passes every frame.
+    frame_time: Duration,
+    /// Buttons pressed per action.
+    held: HashMap<A, Vec<GenericButton>>,
+}
+
+impl<A: InputAction> Default for InputSimulator<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: InputAction> InputSimulator<A> {
+    /// App with `InputPlugin` and `plugin::<A>`.
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin);
+        Self::from_app(app)
+    }
+
+    /// Wrap an existing app. It needs `InputPlugin` (or `DefaultPlugins`).
+    /// `plugin::<A>` and `Time` are added if missing.
+    pub fn from_app(mut app: App) -> Self {
+        if !app.world().contains_resource::<Time>() {
+            app.init_resource::<Time>();
+        }
+        if !app.world().contains_resource::<ActionState<A>>() {
+            app.add_plugins(crate::plugin::<A>);
+        }
+        Self {
+            app,
+            gamepad: Gamepad::new(0),
+            frame_time: Duration::from_secs_f64(1. / 60.),
+            held: HashMap::new(),
+        }
+    }
+
+    /// Set the gamepad that presses device independent gamepad buttons. Defaults to gamepad 0.
+    pub fn with_gamepad(mut self, gamepad: Gamepad) -> Self {
+        self.gamepad = gamepad;
+        self
+    }
+
+    /// Set the time that passes every frame. Defaults to 1/60 s.
+    pub fn with_frame_time(mut self, frame_time: Duration) -> Self {
+        self.frame_time = frame_time;
+        self
+    }
+
+    /// Time that passes every frame.
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// The simulated app.
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// The simulated app, e.g. to add systems under test.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Evaluated state of all actions.
+    pub fn state(&self) -> &ActionState<A> {
+        self.app.world().resource::<ActionState<A>>()
+    }
+
+    /// Press the first button (or else the first chord) of `action` and run one frame.
+    ///
+    /// # Panics
+    /// If `action` has no keyboard, mouse or gamepad binding.
+    pub fn press(&mut self, action: A) -> &mut Self {
+        let buttons = self.binding(&action);
+        for button in buttons.iter() {
+            self.send(*button, true);
+        }
+        self.held.insert(action, buttons);
+        self.advance(1)
+    }
+
+    /// Release the buttons pressed for `action` and run one frame.
+    pub fn release(&mut self, action: A) -> &mut Self {
+        let buttons = match self.held.remove(&action) {
+            Some(buttons) => buttons,
+            None => self.binding(&action),
+        };
+        for button in buttons.iter().rev() {
+            self.send(*button, false);
+        }
+        self.advance(1)
+    }
+
+    /// Press `action`, keep it pressed for `frames` frames in total and release it.
+    pub fn hold_for(&mut self, action: A, frames: usize) -> &mut Self {
+        self.press(action);
+        self.advance(frames.saturating_sub(1));
+        self.release(action)
+    }
+
+    /// Press a single button and run one frame.
+    pub fn press_button(&mut self, button: GenericButton) -> &mut Self {
+        self.send(button, true);
+        self.advance(1)
+    }
+
+    /// Release a single button and run one frame.
+    pub fn release_button(&mut self, button: GenericButton) -> &mut Self {
+        self.send(button, false);
+        self.advance(1)
+    }
+
+    /// Run `frames` frames, each `frame_time` long.
+    pub fn advance(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            if let Some(mut strategy) = self
+                .app
+                .world_mut()
+                .get_resource_mut::<TimeUpdateStrategy>()
+            {
+                *strategy = TimeUpdateStrategy::ManualDuration(self.frame_time);
+            } else {
+                self.app
+                    .world_mut()
+                    .resource_mut::<Time>()
+                    .advance_by(self.frame_time);
+            }
+            self.app.update();
+        }
+        self
+    }
+
+    /// Buttons that press `action`.
+    fn binding(&self, action: &A) -> Vec<GenericButton> {
+        let mapped = self
+            .app
+            .world()
+            .resource::<ButtonMapping<A>>()
+            .get_from_action(action);
+        let simulated = |b: &GenericButton| !matches!(b, GenericButton::Touch(_));
+        mapped
+            .and_then(|m| {
+                m.get_buttons()
+                    .iter()
+                    .find(|b| simulated(b))
+                    .map(|b| vec![*b])
+                    .or_else(|| {
+                        m.get_chords()
+                            .iter()
+                            .find(|c| c.get_buttons().iter().all(simulated))
+                            .map(|c| c.get_buttons().to_vec())
+                    })
+            })
+            .unwrap_or_else(|| {
+                panic!(
+                    "{:?} has no keyboard, mouse or gamepad binding to simulate",
+                    action
+                )
+            })
+    }
+
+    /// Send the device event of pressing or releasing `button`.
+    fn send(&mut self, button: GenericButton, pressed: bool) {
+        let state = if pressed {
+            ButtonState::Pressed
+        } else {
+            ButtonState::Released
+        };
+        let world = self.app.world_mut();
+        match button {
+            GenericButton::KeyBoard(key_code) => {
+                world.send_event(KeyboardInput {
+                    key_code,
+                    logical_key: Key::Unidentified(NativeKey::Unidentified),
+                    state,
+                    window: Entity::PLACEHOLDER,
+                });
+            }
+            GenericButton::Mouse(button) => {
+                world.send_event(MouseButtonInput {
+                    button,
+                    state,
+                    window: Entity::PLACEHOLDER,
+                });
+            }
+            GenericButton::Gamepad(b) => {
+                let value = if pressed { 1. } else { 0. };
+                world.send_event(GamepadButtonChangedEvent::new(
+                    b.gamepad,
+                    b.button_type,
+                    value,
+                ));
+            }
+            GenericButton::AnyGamepad(t) => {
+                let value = if pressed { 1. } else { 0. };
+                world.send_event(GamepadButtonChangedEvent::new(self.gamepad, t, value));
+            }
+            GenericButton::Touch(_) => {}
+        }
+    }
+}
@@ -0,0 +1,457 @@
+//! Touch input as buttons
+//!
+//! `touch_plugin` reads `TouchInput` events into `ButtonInput<TouchButton>`, which `ActionState<A>` evaluates
+//! like any other button source. Virtual on-screen buttons are rectangles in `TouchAreas`, pressed while a finger
+//! is inside (at least for one frame). `GestureRecognizer` turns touches into swipes and pinches (pressed for one frame) and long presses
+//! (pressed until the finger is lifted). Touch buttons can not be part of chords or sequences.
+//!
+//! # Example usage:
+//! ```
+//! use serde::Serialize;
+//! use bevy_app::prelude::*;
+//! use bevy_ecs::entity::Entity;
+//! use bevy_math::{Rect, Vec2};
+//! use bevy_time::Time;
+//! use bevy_tarot_chariot::{ActionState, ButtonMapping, InputAction, MappedButtons, TouchAreas, TouchButton};
+//! use bevy_tarot_chariot::bevy_input::{prelude::*, InputPlugin};
+//! use bevy_tarot_chariot::bevy_input::touch::{TouchInput, TouchPhase};
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Jump,
+//!     Dash,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Jump, TouchButton::Virtual(0).into()));
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Dash, TouchButton::SwipeRight.into()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> TouchInput {
+//!     TouchInput { phase, position: Vec2::new(x, y), window: Entity::PLACEHOLDER, force: None, id }
+//! }
+//!
+//! pub fn main() {
+//!     let mut app = App::new();
+//!     app.add_plugins(InputPlugin);
+//!     app.init_resource::<Time>();
+//!     app.add_plugins(bevy_tarot_chariot::plugin::<SimpleInputAction>);
+//!     app.add_plugins(bevy_tarot_chariot::touch_plugin);
+//!     app.insert_resource(TouchAreas::default().with_area(0, Rect::new(0., 0., 100., 100.)));
+//!     app.world_mut().send_event(touch(1, TouchPhase::Started, 50., 50.));
+//!     app.update();
+//!     assert!(app.world().resource::<ActionState<SimpleInputAction>>().just_pressed(&SimpleInputAction::Jump));
+//!     app.world_mut().send_event(touch(1, TouchPhase::Ended, 50., 50.));
+//!     app.world_mut().send_event(touch(2, TouchPhase::Started, 200., 300.));
+//!     app.world_mut().send_event(touch(2, TouchPhase::Moved, 300., 310.));
+//!     app.world_mut().send_event(touch(2, TouchPhase::Ended, 400., 310.));
+//!     app.update();
+//!     let state = app.world().resource::<ActionState<SimpleInputAction>>();
+//!     assert!(state.just_released(&SimpleInputAction::Jump));
+//!     assert!(state.just_pressed(&SimpleInputAction::Dash));
+//!     app.update();
+//!     assert!(app.world().resource::<ActionState<SimpleInputAction>>().just_released(&SimpleInputAction::Dash));
+//!     app.world_mut().send_event(touch(3, TouchPhase::Started, 20., 20.));
+//!     app.world_mut().send_event(touch(3, TouchPhase::Ended, 20., 20.));
+//!     app.update();
+//!     assert!(app.world().resource::<ActionState<SimpleInputAction>>().just_pressed(&SimpleInputAction::Jump));
+//!     app.update();
+//!     assert!(app.world().resource::<ActionState<SimpleInputAction>>().just_released(&SimpleInputAction::Jump));
+//! }
+//! ```
+
+use crate::ActionStateSystem;
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_input::prelude::*;
+use bevy_input::touch::{TouchInput, TouchPhase};
+use bevy_input::InputSystem;
+use bevy_math::{Rect, Vec2};
+use bevy_time::Time;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Reads `TouchInput` into `ButtonInput<TouchButton>` before action states are updated.
+pub fn touch_plugin(app: &mut App) {
+    app.init_resource::<ButtonInput<TouchButton>>();
+    app.init_resource::<TouchAreas>();
+    app.init_resource::<GestureRecognizer>();
+    app.add_systems(
+        PreUpdate,
+        update_touch_buttons
+            .after(InputSystem)
+            .before(ActionStateSystem),
+    );
+}
+
+/// Virtual on-screen button or recognized gesture.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TouchButton {
+    /// Touch area with the id in `TouchAreas`
+    Virtual(u16),
+    /// Fast movement of a single finger upwards
+    SwipeUp,
+    /// Fast movement of a single finger downwards
+    SwipeDown,
+    /// Fast movement of a single finger to the left
+    SwipeLeft,
+    /// Fast movement of a single finger to the right
+    SwipeRight,
+    /// Two fingers moving towards each other
+    PinchIn,
+    /// Two fingers moving apart
+    PinchOut,
+    /// A single finger resting in place
+    LongPress,
+}
+
+/// Rectangles that act as `TouchButton::Virtual` buttons.
+/// Positions are logical pixels from the top left corner of the window, the same space as UI nodes.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TouchAreas {
+    /// Area per id.
+    areas: HashMap<u16, Rect>,
+}
+
+impl TouchAreas {
+    /// Add the area of the virtual button `id`.
+    pub fn with_area(mut self, id: u16, rect: Rect) -> Self {
+        self.insert(id, rect);
+        self
+    }
+
+    /// Set the area of the virtual button `id`.
+    pub fn insert(&mut self, id: u16, rect: Rect) {
+        self.areas.insert(id, rect);
+    }
+
+    /// Remove the virtual button `id`.
+    pub fn remove(&mut self, id: u16) -> Option<Rect> {
+        self.areas.remove(&id)
+    }
+
+    /// Area of the virtual button `id`.
+    pub fn get(&self, id: u16) -> Option<Rect> {
+        self.areas.get(&id).copied()
+    }
+
+    /// Iterate over all areas.
+    pub fn iter(&self) -> impl Iterator<Item = (u16, Rect)> + '_ {
+        self.areas.iter().map(|(id, rect)| (*id, *rect))
+    }
+}
+
+/// Thresholds of the gesture recognizer. Distances are in logical pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GestureSettings {
+    /// Minimum distance of a swipe.
+    pub swipe_distance: f32,
+    /// Maximum duration of a swipe.
+    pub swipe_time: Duration,
+    /// Change of the distance between two fingers that counts as a pinch.
+    pub pinch_distance: f32,
+    /// Time a finger has to rest until it is a long press.
+    pub long_press: Duration,
+    /// Distance a finger can move during a long press.
+    pub long_press_tolerance: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            swipe_distance: 80.,
+            swipe_time: Duration::from_millis(400),
+            pinch_distance: 40.,
+            long_press: Duration::from_millis(600),
+            long_press_tolerance: 10.,
+        }
+    }
+}
+
+impl GestureSettings {
+    /// Set the minimum distance and maximum duration of a swipe.
+    pub fn with_swipe(mut self, distance: f32, time: Duration) -> Self {
+        self.swipe_distance = distance;
+        self.swipe_time = time;
+        self
+    }
+
+    /// Set the distance change that counts as a pinch.
+    pub fn with_pinch(mut self, distance: f32) -> Self {
+        self.pinch_distance = distance;
+        self
+    }
+
+    /// Set the time and movement tolerance of a long press.
+    pub fn with_long_press(mut self, time: Duration, tolerance: f32) -> Self {
+        self.long_press = time;
+        self.long_press_tolerance = tolerance;
+        self
+    }
+}
+
+/// A finger on the screen.
+#[derive(Copy, Clone, Debug)]
+struct TrackedTouch {
+    /// Position where the touch started.
+    start: Vec2,
+    /// Current position.
+    position: Vec2,
+    /// Time when the touch started.
+    started: Duration,
+    /// False once the finger left the long press tolerance.
+    resting: bool,
+}
+
+/// Recognizes gestures from `TouchInput` events.
+/// Can be fed events directly with `handle`, e.g. in tests.
+///
+/// # Example usage:
+/// ```
+/// use std::time::Duration;
+/// use bevy_ecs::entity::Entity;
+/// use bevy_math::Vec2;
+/// use bevy_tarot_chariot::{GestureRecognizer, TouchButton};
+/// use bevy_tarot_chariot::bevy_input::touch::{TouchInput, TouchPhase};
+/// fn touch(id: u64, phase: TouchPhase, x: f32) -> TouchInput {
+///     TouchInput { phase, position: Vec2::new(x, 0.), window: Entity::PLACEHOLDER, force: None, id }
+/// }
+///
+/// let mut recognizer = GestureRecognizer::default();
+/// recognizer.handle(&touch(1, TouchPhase::Started, 100.), Duration::ZERO);
+/// recognizer.update(Duration::from_secs(1));
+/// assert!(recognizer.long_pressing());
+/// recognizer.handle(&touch(2, TouchPhase::Started, 200.), Duration::from_secs(1));
+/// recognizer.handle(&touch(2, TouchPhase::Moved, 300.), Duration::from_secs(1));
+/// recognizer.update(Duration::from_secs(1));
+/// assert!(!recognizer.long_pressing());
+/// assert_eq!(recognizer.drain().collect::<Vec<_>>(), vec![TouchButton::PinchOut]);
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct GestureRecognizer {
+    /// Thresholds
+    pub settings: GestureSettings,
+    /// Fingers currently on the screen.
+    touches: HashMap<u64, TrackedTouch>,
+    /// Distance between two fingers at the last pinch.
+    pinch_reference: Option<f32>,
+    /// More than one finger touched the screen since all fingers were lifted.
+    multi_touch: bool,
+    /// Gestures recognized since the last `drain`.
+    recognized: Vec<TouchButton>,
+    /// A long press is active.
+    long_pressing: bool,
+    /// Start positions of touches since the last `take_started`.
+    started: Vec<Vec2>,
+}
+
+impl GestureRecognizer {
+    /// Recognizer with custom thresholds.
+    pub fn new(settings: GestureSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Track a touch event that happened at the elapsed time `now`.
+    pub fn handle(&mut self, event: &TouchInput, now: Duration) {
+        match event.phase {
+            TouchPhase::Started => {
+                self.touches.insert(
+                    event.id,
+                    TrackedTouch {
+                        start: event.position,
+                        position: event.position,
+                        started: now,
+                        resting: true,
+                    },
+                );
+                self.started.push(event.position);
+                if self.touches.len() > 1 {
+                    self.multi_touch = true;
+                    self.pinch_reference = self.finger_distance();
+                }
+            }
+            TouchPhase::Moved => {
+                let Some(touch) = self.touches.get_mut(&event.id) else {
+                    return;
+                };
+                touch.position = event.position;
+                if touch.start.distance(touch.position) > self.settings.long_press_tolerance {
+                    touch.resting = false;
+                }
+                self.recognize_pinch();
+            }
+            TouchPhase::Ended => {
+                let Some(mut touch) = self.touches.remove(&event.id) else {
+                    return;
+                };
+                touch.position = event.position;
+                if !self.multi_touch {
+                    if let Some(swipe) = self.recognize_swipe(&touch, now) {
+                        self.recognized.push(swipe);
+                    }
+                }
+                self.lift();
+            }
+            TouchPhase::Canceled => {
+                self.touches.remove(&event.id);
+                self.lift();
+            }
+        }
+    }
+
+    /// Check for long presses at the elapsed time `now`.
+    pub fn update(&mut self, now: Duration) {
+        self.long_pressing = !self.multi_touch
+            && self
+                .touches
+                .values()
+                .any(|t| t.resting && now.saturating_sub(t.started) >= self.settings.long_press);
+    }
+
+    /// Take all swipes and pinches recognized since the last call.
+    pub fn drain(&mut self) -> impl Iterator<Item = TouchButton> + '_ {
+        self.recognized.drain(..)
+    }
+
+    /// Take the start positions of all touches that started since the last call,
+    /// including touches that already ended.
+    pub fn take_started(&mut self) -> Vec<Vec2> {
+        std::mem::take(&mut self.started)
+    }
+
+    /// Check if a long press is active.
+    pub fn long_pressing(&self) -> bool {
+        self.long_pressing
+    }
+
+    /// Positions of all fingers on the screen.
+    pub fn positions(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.touches.values().map(|t| t.position)
+    }
+
+    /// Distance between the two oldest fingers.
+    fn finger_distance(&self) -> Option<f32> {
+        let mut ids = self.touches.keys().copied().collect::<Vec<_>>();
+        ids.sort_unstable();
+        match ids[..] {
+            [a, b, ..] => Some(
+                self.touches[&a]
+                    .position
+                    .distance(self.touches[&b].position),
+            ),
+            _ => None,
+        }
+    }
+
+    fn recognize_pinch(&mut self) {
+        let (Some(reference), Some(distance)) = (self.pinch_reference, self.finger_distance())
+        else {
+            return;
+        };
+        if distance - reference >= self.settings.pinch_distance {
+            self.recognized.push(TouchButton::PinchOut);
+            self.pinch_reference = Some(distance);
+        } else if reference - distance >= self.settings.pinch_distance {
+            self.recognized.push(TouchButton::PinchIn);
+            self.pinch_reference = Some(distance);
+        }
+    }
+
+    fn recognize_swipe(&self, touch: &TrackedTouch, now: Duration) -> Option<TouchButton> {
+        let delta = touch.position - touch.start;
+        if delta.length() < self.settings.swipe_distance
+            || now.saturating_sub(touch.started) > self.settings.swipe_time
+        {
+            return None;
+        }
+        // Window coordinates grow downwards.
+        Some(if delta.x.abs() >= delta.y.abs() {
+            if delta.x > 0. {
+                TouchButton::SwipeRight
+            } else {
+                TouchButton::SwipeLeft
+            }
+        } else if delta.y > 0. {
+            TouchButton::SwipeDown
+        } else {
+            TouchButton::SwipeUp
+        })
+    }
+
+    /// Reset multi touch state after a finger was lifted.
+    fn lift(&mut self) {
+        if self.touches.len() < 2 {
+            self.pinch_reference = None;
+        }
+        if self.touches.is_empty() {
+            self.multi_touch = false;
+        }
+    }
+}
+
+/// Writes touch areas and recognized gestures into `ButtonInput<TouchButton>`.
+/// Swipes and pinches are released one frame after they were pressed.
+/// Touch areas are pressed for at least one frame, even if the touch started and ended in the same frame.
+pub fn update_touch_buttons(
+    mut input: ResMut<ButtonInput<TouchButton>>,
+    mut recognizer: ResMut<GestureRecognizer>,
+    mut events: EventReader<TouchInput>,
+    areas: Res<TouchAreas>,
+    time: Res<Time>,
+) {
+    input.clear();
+    let one_frame = input
+        .get_pressed()
+        .filter(|b| !matches!(b, TouchButton::Virtual(_) | TouchButton::LongPress))
+        .copied()
+        .collect::<Vec<_>>();
+    for button in one_frame {
+        input.release(button);
+    }
+    for event in events.read() {
+        recognizer.handle(event, time.elapsed());
+    }
+    recognizer.update(time.elapsed());
+    for gesture in recognizer.drain() {
+        input.press(gesture);
+    }
+    set_pressed(
+        &mut input,
+        TouchButton::LongPress,
+        recognizer.long_pressing(),
+    );
+    let started = recognizer.take_started();
+    for (id, rect) in areas.iter() {
+        let touched = recognizer
+            .positions()
+            .chain(started.iter().copied())
+            .any(|p| rect.contains(p));
+        set_pressed(&mut input, TouchButton::Virtual(id), touched);
+    }
+    let removed = input
+        .get_pressed()
+        .filter_map(|b| match b {
+            TouchButton::Virtual(id) if areas.get(*id).is_none() => Some(*b),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for button in removed {
+        input.release(button);
+    }
+}
+
+fn set_pressed(input: &mut ButtonInput<TouchButton>, button: TouchButton, pressed: bool) {
+    if pressed && !input.pressed(button) {
+        input.press(button);
+    } else if !pressed && input.pressed(button) {
+        input.release(button);
+    }
+}
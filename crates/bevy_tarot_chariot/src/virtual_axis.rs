@@ -0,0 +1,168 @@
+//! Virtual axes composed from actions
+//!
+//! `VirtualAxis` combines a positive and a negative action, `VirtualDPad` four directions.
+//! If opposite directions are both pressed, the one pressed last wins. Analog actions are added on top
+//! and the result is clamped, so a diagonal never moves faster than a straight line.
+//!
+//! # Example usage:
+//! ```
+//! use std::time::Duration;
+//! use serde::Serialize;
+//! use bevy_tarot_chariot::{ActionState, ButtonMapping, InputAction, MappedButtons, VirtualDPad};
+//! use bevy_tarot_chariot::bevy_input::prelude::*;
+//! #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+//! pub enum SimpleInputAction {
+//!     Up,
+//!     Down,
+//!     Left,
+//!     Right,
+//! }
+//!
+//! impl InputAction for SimpleInputAction {
+//!     fn default_mapping() -> ButtonMapping<Self> {
+//!         let mut button_mapping = ButtonMapping::default();
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Up, KeyCode::KeyW.into()));
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Down, KeyCode::KeyS.into()));
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Left, KeyCode::KeyA.into()));
+//!         button_mapping.insert_mapping(MappedButtons::new_single(SimpleInputAction::Right, KeyCode::KeyD.into()));
+//!         button_mapping
+//!     }
+//! }
+//!
+//! const MOVE: VirtualDPad<SimpleInputAction> = VirtualDPad::new(
+//!     SimpleInputAction::Up,
+//!     SimpleInputAction::Down,
+//!     SimpleInputAction::Left,
+//!     SimpleInputAction::Right,
+//! );
+//!
+//! pub fn main() {
+//!     let mut state = ActionState::<SimpleInputAction>::default();
+//!     state.get_mut(SimpleInputAction::Up).pressed = true;
+//!     state.get_mut(SimpleInputAction::Up).value = 1.;
+//!     state.get_mut(SimpleInputAction::Right).pressed = true;
+//!     state.get_mut(SimpleInputAction::Right).value = 1.;
+//!     assert!((MOVE.value(&state).length() - 1.).abs() < 0.001);
+//!     assert!(MOVE.value(&state).x > 0. && MOVE.value(&state).y > 0.);
+//!     state.get_mut(SimpleInputAction::Right).held_duration = Duration::from_millis(100);
+//!     state.get_mut(SimpleInputAction::Left).pressed = true;
+//!     state.get_mut(SimpleInputAction::Left).value = 1.;
+//!     assert!(MOVE.value(&state).x < 0.);
+//! }
+//! ```
+
+use crate::{ActionState, InputAction};
+use bevy_math::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Axis from -1 to 1 composed from two actions and an optional analog action.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VirtualAxis<A: InputAction> {
+    /// Action that moves towards 1.
+    pub positive: A,
+    /// Action that moves towards -1.
+    pub negative: A,
+    /// Action whose analog value is added.
+    pub analog: Option<A>,
+}
+
+impl<A: InputAction> VirtualAxis<A> {
+    /// Simpel Constructor
+    pub const fn new(positive: A, negative: A) -> Self {
+        Self {
+            positive,
+            negative,
+            analog: None,
+        }
+    }
+
+    /// Add the analog value of `action`.
+    pub const fn with_analog(mut self, action: A) -> Self {
+        self.analog = Some(action);
+        self
+    }
+
+    /// Current value of the axis.
+    pub fn value(&self, state: &ActionState<A>) -> f32 {
+        let analog = self.analog.map(|a| state.value(&a)).unwrap_or_default();
+        (digital_value(state, &self.positive, &self.negative) + analog).clamp(-1., 1.)
+    }
+}
+
+/// Direction composed from four actions and an optional stick action. Up is positive y.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VirtualDPad<A: InputAction> {
+    /// Action that moves towards positive y.
+    pub up: A,
+    /// Action that moves towards negative y.
+    pub down: A,
+    /// Action that moves towards negative x.
+    pub left: A,
+    /// Action that moves towards positive x.
+    pub right: A,
+    /// Action whose dual axis value is added.
+    pub stick: Option<A>,
+}
+
+impl<A: InputAction> VirtualDPad<A> {
+    /// Simpel Constructor
+    pub const fn new(up: A, down: A, left: A, right: A) -> Self {
+        Self {
+            up,
+            down,
+            left,
+            right,
+            stick: None,
+        }
+    }
+
+    /// Add the dual axis value of `action`.
+    pub const fn with_stick(mut self, action: A) -> Self {
+        self.stick = Some(action);
+        self
+    }
+
+    /// Horizontal axis of the D-pad.
+    pub fn x_axis(&self) -> VirtualAxis<A> {
+        VirtualAxis::new(self.right, self.left)
+    }
+
+    /// Vertical axis of the D-pad.
+    pub fn y_axis(&self) -> VirtualAxis<A> {
+        VirtualAxis::new(self.up, self.down)
+    }
+
+    /// Current direction, at most of length 1.
+    pub fn value(&self, state: &ActionState<A>) -> Vec2 {
+        let digital = Vec2::new(
+            digital_value(state, &self.right, &self.left),
+            digital_value(state, &self.up, &self.down),
+        );
+        let stick = self.stick.map(|a| state.axis_pair(&a)).unwrap_or_default();
+        (digital + stick).clamp_length_max(1.)
+    }
+}
+
+/// Value of two opposing actions. If both are pressed, the one pressed last wins.
+fn digital_value<A: InputAction>(state: &ActionState<A>, positive: &A, negative: &A) -> f32 {
+    let strength = |a: &A| {
+        if state.pressed(a) {
+            state.value(a).abs().min(1.)
+        } else {
+            0.
+        }
+    };
+    match (state.pressed(positive), state.pressed(negative)) {
+        (true, false) => strength(positive),
+        (false, true) => -strength(negative),
+        (true, true) => {
+            let (p, n) = (state.held_duration(positive), state.held_duration(negative));
+            match p.cmp(&n) {
+                std::cmp::Ordering::Less => strength(positive),
+                std::cmp::Ordering::Greater => -strength(negative),
+                std::cmp::Ordering::Equal => 0.,
+            }
+        }
+        (false, false) => 0.,
+    }
+}
@@ -0,0 +1,259 @@
+#![warn(missing_docs)]
+//! Derive macro for `bevy_tarot_chariot::InputAction`
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use std::collections::HashMap;
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Path, Token};
+
+/// Implements `InputAction` with a `default_mapping()` built from `#[bind(...)]` attributes on the variants.
+///
+/// A binding is a key (`KeyW` or `KeyCode::KeyW`), a mouse button (`MouseButton::Left`) or a gamepad button
+/// (`GamepadButton::South`, bound on any gamepad) or a gesture (`TouchButton::SwipeLeft`).
+/// Buttons joined with `+` form a chord.
+/// `#[context = "editor"]` puts the mapping of a variant into an `InputContext`.
+/// Binding the same button or chord twice in the same context is a compile error.
+/// Chords are compared regardless of order and modifier side (`ControlLeft + KeyS` equals `KeyS + ControlRight`).
+///
+/// # Example usage:
+/// ```
+/// use serde::Serialize;
+/// use bevy_tarot_chariot::{InputAction, TouchButton};
+/// use bevy_tarot_chariot::bevy_input::prelude::*;
+/// #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize, InputAction)]
+/// pub enum SimpleInputAction {
+///     #[bind(KeyW, GamepadButton::DPadUp)]
+///     Up,
+///     #[bind(ControlLeft + KeyS)]
+///     Save,
+///     #[bind(KeyE, TouchButton::SwipeRight)]
+///     Dash,
+///     #[bind(ControlLeft + KeyS)]
+///     #[context = "editor"]
+///     SaveLevel,
+///     Unbound,
+/// }
+///
+/// let mapping = SimpleInputAction::default_mapping();
+/// assert_eq!(mapping.get_buttons(&SimpleInputAction::Up), Some(&[KeyCode::KeyW.into(), GamepadButtonType::DPadUp.into()][..]));
+/// assert_eq!(mapping.get_from_action(&SimpleInputAction::Save).unwrap().get_chords().len(), 1);
+/// assert_eq!(mapping.get_buttons(&SimpleInputAction::Dash).unwrap()[1], TouchButton::SwipeRight.into());
+/// assert_eq!(mapping.get_from_action(&SimpleInputAction::SaveLevel).unwrap().get_context(), Some("editor"));
+/// assert!(mapping.get_from_action(&SimpleInputAction::Unbound).is_none());
+/// ```
+///
+/// ```compile_fail
+/// use serde::Serialize;
+/// use bevy_tarot_chariot::InputAction;
+/// #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize, InputAction)]
+/// pub enum SimpleInputAction {
+///     #[bind(KeyW)]
+///     Up,
+///     #[bind(KeyCode::KeyW)]
+///     Jump,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use serde::Serialize;
+/// use bevy_tarot_chariot::InputAction;
+/// #[derive(Copy, Clone, Hash, Debug, PartialEq, Eq, Serialize, InputAction)]
+/// pub enum SimpleInputAction {
+///     #[bind(ControlLeft + KeyS)]
+///     Save,
+///     #[bind(KeyS + ControlRight)]
+///     Store,
+/// }
+/// ```
+#[proc_macro_derive(InputAction, attributes(bind, context))]
+pub fn derive_input_action(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// A single button in a `#[bind(...)]` attribute.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+enum Button {
+    Key(String),
+    Mouse(String),
+    Gamepad(String),
+    Touch(String),
+}
+
+impl Button {
+    fn from_path(path: &Path) -> syn::Result<Self> {
+        let segments = path
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect::<Vec<_>>();
+        match segments.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+            [key] => Ok(Button::Key(key.to_string())),
+            ["KeyCode", key] => Ok(Button::Key(key.to_string())),
+            ["MouseButton", button] => Ok(Button::Mouse(button.to_string())),
+            ["GamepadButton" | "GamepadButtonType", button] => {
+                Ok(Button::Gamepad(button.to_string()))
+            }
+            ["TouchButton", button] => Ok(Button::Touch(button.to_string())),
+            _ => Err(syn::Error::new_spanned(
+                path,
+                "expected a KeyCode, MouseButton::_, GamepadButton::_ or TouchButton::_",
+            )),
+        }
+    }
+
+    /// Same button with right modifiers replaced by left ones, for comparing chords.
+    fn normalized(&self) -> Self {
+        match self {
+            Button::Key(k) => Button::Key(match k.as_str() {
+                "ControlRight" => "ControlLeft".to_string(),
+                "ShiftRight" => "ShiftLeft".to_string(),
+                "AltRight" => "AltLeft".to_string(),
+                "SuperRight" => "SuperLeft".to_string(),
+                _ => k.clone(),
+            }),
+            other => other.clone(),
+        }
+    }
+
+    fn to_tokens(&self, span: proc_macro2::Span) -> TokenStream2 {
+        let (ty, name) = match self {
+            Button::Key(k) => (quote!(::bevy_tarot_chariot::bevy_input::prelude::KeyCode), k),
+            Button::Mouse(m) => (quote!(::bevy_tarot_chariot::bevy_input::prelude::MouseButton), m),
+            Button::Gamepad(g) => (
+                quote!(::bevy_tarot_chariot::bevy_input::prelude::GamepadButtonType),
+                g,
+            ),
+            Button::Touch(t) => (quote!(::bevy_tarot_chariot::TouchButton), t),
+        };
+        let name = syn::Ident::new(name, span);
+        quote_spanned! {span=>
+            ::bevy_tarot_chariot::GenericButton::from(#ty::#name)
+        }
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "InputAction can only be derived for enums",
+        ));
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut bound = HashMap::<(Option<String>, Vec<Button>), syn::Ident>::new();
+    let mut mappings = vec![];
+    for variant in data.variants.iter() {
+        let mut buttons = vec![];
+        let mut chords = vec![];
+        let context = variant_context(variant)?;
+        for attr in variant.attrs.iter().filter(|a| a.path().is_ident("bind")) {
+            if !matches!(variant.fields, Fields::Unit) {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "#[bind] is only supported on unit variants",
+                ));
+            }
+            let items = attr.parse_args_with(|input: ParseStream| {
+                Punctuated::<_, Token![,]>::parse_terminated_with(input, parse_binding)
+            })?;
+            for item in items {
+                let binding = item
+                    .iter()
+                    .map(Button::from_path)
+                    .collect::<syn::Result<Vec<_>>>()?;
+                let key = (context.clone(), binding_key(&binding));
+                if let Some(other) = bound.get(&key) {
+                    return Err(syn::Error::new_spanned(
+                        &item,
+                        format!("binding is already used by `{}`", other),
+                    ));
+                }
+                bound.insert(key, variant.ident.clone());
+                let tokens = binding
+                    .iter()
+                    .map(|b| b.to_tokens(item.span()))
+                    .collect::<Vec<_>>();
+                if tokens.len() == 1 {
+                    buttons.push(tokens[0].clone());
+                } else {
+                    chords.push(quote! {
+                        .with_chord(::bevy_tarot_chariot::ButtonChord::new(&[#(#tokens),*]))
+                    });
+                }
+            }
+        }
+        if buttons.is_empty() && chords.is_empty() {
+            continue;
+        }
+        let ident = &variant.ident;
+        let context = context.map(|c| quote!(.in_context(#c)));
+        mappings.push(quote! {
+            mapping.insert_mapping(
+                ::bevy_tarot_chariot::MappedButtons::new(#name::#ident, &[#(#buttons),*])
+                    #(#chords)*
+                    #context
+            );
+        });
+    }
+    Ok(quote! {
+        impl #impl_generics ::bevy_tarot_chariot::InputAction for #name #ty_generics #where_clause {
+            fn default_mapping() -> ::bevy_tarot_chariot::ButtonMapping<Self> {
+                let mut mapping = ::bevy_tarot_chariot::ButtonMapping::default();
+                #(#mappings)*
+                mapping
+            }
+        }
+    })
+}
+
+/// Context of a variant from `#[context = "..."]`.
+fn variant_context(variant: &syn::Variant) -> syn::Result<Option<String>> {
+    let mut context = None;
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("context"))
+    {
+        if context.is_some() {
+            return Err(syn::Error::new_spanned(attr, "duplicate #[context]"));
+        }
+        let value = &attr.meta.require_name_value()?.value;
+        let syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(name),
+            ..
+        }) = value
+        else {
+            return Err(syn::Error::new_spanned(
+                value,
+                "expected #[context = \"name\"]",
+            ));
+        };
+        context = Some(name.value());
+    }
+    Ok(context)
+}
+
+/// Key for the duplicate check. Chords are compared as sets with left and right modifiers treated as equal.
+fn binding_key(binding: &[Button]) -> Vec<Button> {
+    if binding.len() < 2 {
+        return binding.to_vec();
+    }
+    let mut key = binding.iter().map(Button::normalized).collect::<Vec<_>>();
+    key.sort();
+    key.dedup();
+    key
+}
+
+/// Parses `KeyW` or `ControlLeft + KeyS`.
+fn parse_binding(input: ParseStream) -> syn::Result<Punctuated<Path, Token![+]>> {
+    Punctuated::<Path, Token![+]>::parse_separated_nonempty(input)
+}
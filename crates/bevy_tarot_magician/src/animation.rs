@@ -1,22 +1,504 @@
+//! Sprite sheet animations
+//!
+//! An `AnimationSet` (`{name}_anim.ron` next to the sprite) holds named `Animation`s. `AnimationPlayer` plays one
+//! of them on the `TextureAtlas` of its entity and triggers `AnimationFinished` when a RunOnce or Reverse
+//! animation ends.
+//!
+//! # Example usage:
+//! ```
+//! use std::time::Duration;
+//! use bevy_tarot_magician::animation::{Animation, AnimationBehaviour, AnimationPlayer, AnimationSet};
+//! let set = AnimationSet::default()
+//!     .with_animation("run", Animation::new(vec![4, 5, 6], AnimationBehaviour::Loop));
+//! let mut player = set.player("run").unwrap();
+//! assert_eq!(player.index(), Some(4));
+//! player.tick(Duration::from_millis(250));
+//! assert_eq!(player.index(), Some(6));
+//! player.tick(Duration::from_millis(50));
+//! assert_eq!(player.index(), Some(4));
+//! assert!(!player.is_finished());
+//! ```
+
+use crate::{load_asset, AssetPathMap, HandleMap, MagicianError, SpriteAssetKey, TarotAsset};
+use bevy_asset::io::Reader;
+use bevy_asset::prelude::*;
+use bevy_asset::{ron, AssetLoader, AsyncReadExt, LoadContext};
+use bevy_ecs::prelude::*;
+use bevy_reflect::TypePath;
+use bevy_sprite::TextureAtlas;
+use bevy_time::Time;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Handle map for `Handle<AnimationSet>`
+pub type AnimationSetHandleMap<K> = HandleMap<K, AnimationSet>;
+
+/// Load Asset wrapper for `Handle<AnimationSet>` (`{name}_anim.ron` next to the sprite)
+pub fn load_animation_set<K: SpriteAssetKey>(
+    key: K,
+    sprite_paths: &AssetPathMap<K>,
+    animation_set_handle_map: &mut AnimationSetHandleMap<K>,
+    asset_server: &AssetServer,
+) -> Result<Handle<AnimationSet>, MagicianError> {
+    load_asset(key, sprite_paths, animation_set_handle_map, asset_server)
+}
+
+/// Named animations of a sprite sheet, e.g. "idle", "run" and "jump".
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AnimationSet {
+    /// Animation per name
+    pub animations: BTreeMap<String, Animation>,
+}
+
+impl AnimationSet {
+    /// Add the animation `name`.
+    pub fn with_animation(mut self, name: impl Into<String>, animation: Animation) -> Self {
+        self.animations.insert(name.into(), animation);
+        self
+    }
+
+    /// Animation with `name`.
+    pub fn get(&self, name: &str) -> Option<&Animation> {
+        self.animations.get(name)
+    }
+
+    /// Names of all animations.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.animations.keys()
+    }
+
+    /// Player for the animation with `name`.
+    pub fn player(&self, name: &str) -> Option<AnimationPlayer> {
+        self.get(name).cloned().map(AnimationPlayer::new)
+    }
+}
+
+impl TarotAsset for AnimationSet {
+    fn file_extension() -> Option<&'static str> {
+        Some("ron")
+    }
+
+    fn file_suffix() -> Option<&'static str> {
+        Some("_anim")
+    }
+}
+
+/// AssetLoader for `AnimationSet`
+#[derive(Default)]
+pub struct AnimationSetLoader {}
+
+/// Loading errors for `AnimationSetLoader`
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AnimationSetLoadingError {
+    /// An [IO](std::io) Error
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
 
-/// TODO
-#[derive(Serialize, Deserialize)]
+impl AssetLoader for AnimationSetLoader {
+    type Asset = AnimationSet;
+    type Settings = ();
+    type Error = AnimationSetLoadingError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        ron::de::from_bytes::<AnimationSet>(&bytes).map_err(|e| e.into())
+    }
+}
+
+/// Sequence of sprite sheet indices.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Animation {
-    /// TODO
-    key_frames: Vec<usize>,
-    /// TODO
-    behaviour: AnimationBehaviour,
+    /// Sprite sheet indices in playing order.
+    pub key_frames: Vec<usize>,
+    /// What happens at the end of the key frames.
+    #[serde(default)]
+    pub behaviour: AnimationBehaviour,
+    /// How long every key frame is shown.
+    #[serde(default = "default_frame_duration")]
+    pub frame_duration: Duration,
+    /// How long each key frame is shown, overrides `frame_duration` for the key frames it covers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frame_durations: Vec<Duration>,
+}
+
+fn default_frame_duration() -> Duration {
+    Duration::from_millis(100)
+}
+
+impl Animation {
+    /// Simpel Constructor
+    pub fn new(key_frames: Vec<usize>, behaviour: AnimationBehaviour) -> Self {
+        Self {
+            key_frames,
+            behaviour,
+            frame_duration: default_frame_duration(),
+            frame_durations: vec![],
+        }
+    }
+
+    /// Set how long every key frame is shown.
+    pub fn with_frame_duration(mut self, frame_duration: Duration) -> Self {
+        self.frame_duration = frame_duration;
+        self
+    }
+
+    /// Set how long each key frame is shown.
+    pub fn with_frame_durations(mut self, frame_durations: Vec<Duration>) -> Self {
+        self.frame_durations = frame_durations;
+        self
+    }
+
+    /// How long the key frame at `frame` is shown.
+    pub fn duration(&self, frame: usize) -> Duration {
+        self.frame_durations
+            .get(frame)
+            .copied()
+            .unwrap_or(self.frame_duration)
+    }
+
+    /// Number of key frames.
+    pub fn len(&self) -> usize {
+        self.key_frames.len()
+    }
+
+    /// Animation has no key frames.
+    pub fn is_empty(&self) -> bool {
+        self.key_frames.is_empty()
+    }
 }
 
-/// TODO
-#[derive(Default, Serialize, Deserialize)]
+/// Playback behaviour of an `Animation`.
+#[derive(Default, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum AnimationBehaviour {
-    /// TODO
+    /// Play once and stop on the last key frame.
     #[default]
     RunOnce,
-    /// TODO
+    /// Start again at the first key frame.
     Loop,
-    /// TODO
+    /// Play once backwards and stop on the first key frame.
     Reverse,
+    /// Play forwards and backwards in a loop.
+    PingPong,
+}
+
+/// Plays an `Animation` on the `TextureAtlas` of the entity, e.g. a sprite added by `AddSpriteToEntity`.
+#[derive(Component, Clone, Debug)]
+pub struct AnimationPlayer {
+    /// Played animation
+    animation: Animation,
+    /// Position in the key frames.
+    frame: usize,
+    /// Time spent on the current key frame.
+    elapsed: Duration,
+    /// Direction of a ping-pong animation.
+    backwards: bool,
+    /// Playback is paused.
+    paused: bool,
+    /// A RunOnce or Reverse animation reached its end.
+    finished: bool,
+    /// Playback speed factor, clamped to `0..=MAX_SPEED` while playing.
+    pub speed: f32,
+}
+
+/// Highest playback speed factor of an `AnimationPlayer`.
+pub const MAX_SPEED: f32 = 1000.;
+
+impl AnimationPlayer {
+    /// Play `animation` from its start.
+    pub fn new(animation: Animation) -> Self {
+        let mut player = Self {
+            animation,
+            frame: 0,
+            elapsed: Duration::ZERO,
+            backwards: false,
+            paused: false,
+            finished: false,
+            speed: 1.,
+        };
+        player.restart();
+        player
+    }
+
+    /// Set the playback speed factor.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Played animation.
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    /// Switch to `animation` and play it from its start.
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+        self.restart();
+    }
+
+    /// Resume playback.
+    pub fn play(&mut self) {
+        self.paused = false;
+    }
+
+    /// Pause playback.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Check if playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Check if a RunOnce or Reverse animation reached its end.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Play from the start again.
+    pub fn restart(&mut self) {
+        let start = match self.animation.behaviour {
+            AnimationBehaviour::Reverse => self.animation.len().saturating_sub(1),
+            _ => 0,
+        };
+        self.seek(start);
+        self.backwards = self.animation.behaviour == AnimationBehaviour::Reverse;
+    }
+
+    /// Jump to the key frame at `frame`. A ping-pong animation keeps its direction.
+    pub fn seek(&mut self, frame: usize) {
+        self.frame = frame.min(self.animation.len().saturating_sub(1));
+        self.elapsed = Duration::ZERO;
+        self.finished = false;
+    }
+
+    /// Position in the key frames.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Sprite sheet index of the current key frame.
+    pub fn index(&self) -> Option<usize> {
+        self.animation.key_frames.get(self.frame).copied()
+    }
+
+    /// Advance playback by `delta`. Returns true if the animation finished.
+    pub fn tick(&mut self, delta: Duration) -> bool {
+        if self.paused || self.finished || self.animation.is_empty() {
+            return false;
+        }
+        if self.animation.duration(self.frame).is_zero() {
+            return false;
+        }
+        let speed = if self.speed.is_nan() {
+            0.
+        } else {
+            self.speed.clamp(0., MAX_SPEED)
+        };
+        self.elapsed += delta.mul_f32(speed);
+        loop {
+            // Key frames without duration stop the playback.
+            let frame_duration = self.animation.duration(self.frame);
+            if frame_duration.is_zero() || self.elapsed < frame_duration {
+                return false;
+            }
+            self.elapsed -= frame_duration;
+            if self.step() {
+                self.finished = true;
+                self.elapsed = Duration::ZERO;
+                return true;
+            }
+        }
+    }
+
+    /// Move to the next key frame. Returns true if there is none.
+    fn step(&mut self) -> bool {
+        let last = self.animation.len() - 1;
+        if last == 0 {
+            return matches!(
+                self.animation.behaviour,
+                AnimationBehaviour::RunOnce | AnimationBehaviour::Reverse
+            );
+        }
+        match self.animation.behaviour {
+            AnimationBehaviour::RunOnce => {
+                if self.frame >= last {
+                    return true;
+                }
+                self.frame += 1;
+            }
+            AnimationBehaviour::Loop => self.frame = (self.frame + 1) % (last + 1),
+            AnimationBehaviour::Reverse => {
+                if self.frame == 0 {
+                    return true;
+                }
+                self.frame -= 1;
+            }
+            AnimationBehaviour::PingPong => {
+                if self.backwards && self.frame == 0 || !self.backwards && self.frame >= last {
+                    self.backwards = !self.backwards;
+                }
+                if self.backwards {
+                    self.frame -= 1;
+                } else {
+                    self.frame += 1;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Triggered when a RunOnce or Reverse animation of `entity` reached its end.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct AnimationFinished {
+    /// Entity id
+    pub entity: Entity,
+}
+
+/// Advances all `AnimationPlayer`s and writes the current key frame into their `TextureAtlas`.
+/// The sprite anchor follows the index in `update_sprite_anchors`.
+pub fn play_animations(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut AnimationPlayer, &mut TextureAtlas)>,
+    mut commands: Commands,
+) {
+    for (entity, mut player, mut atlas) in query.iter_mut() {
+        if player.tick(time.delta()) {
+            commands.trigger_targets(AnimationFinished { entity }, entity);
+        }
+        if let Some(index) = player.index() {
+            if atlas.index != index {
+                atlas.index = index;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    fn player(key_frames: Vec<usize>, behaviour: AnimationBehaviour) -> AnimationPlayer {
+        AnimationPlayer::new(Animation::new(key_frames, behaviour).with_frame_duration(FRAME))
+    }
+
+    fn indices(player: &mut AnimationPlayer, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                player.tick(FRAME);
+                player.index().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn run_once() {
+        let mut player = player(vec![1, 2, 3], AnimationBehaviour::RunOnce);
+        assert_eq!(player.index(), Some(1));
+        assert_eq!(indices(&mut player, 2), vec![2, 3]);
+        assert!(!player.is_finished());
+        assert!(player.tick(FRAME));
+        assert!(player.is_finished());
+        assert_eq!(player.index(), Some(3));
+        assert!(!player.tick(FRAME));
+    }
+
+    #[test]
+    fn looping() {
+        let mut player = player(vec![1, 2, 3], AnimationBehaviour::Loop);
+        assert_eq!(indices(&mut player, 4), vec![2, 3, 1, 2]);
+        assert!(!player.is_finished());
+    }
+
+    #[test]
+    fn reverse() {
+        let mut player = player(vec![1, 2, 3], AnimationBehaviour::Reverse);
+        assert_eq!(player.index(), Some(3));
+        assert_eq!(indices(&mut player, 2), vec![2, 1]);
+        assert!(player.tick(FRAME));
+        assert_eq!(player.index(), Some(1));
+    }
+
+    #[test]
+    fn ping_pong() {
+        let mut player = player(vec![1, 2, 3], AnimationBehaviour::PingPong);
+        assert_eq!(indices(&mut player, 6), vec![2, 3, 2, 1, 2, 3]);
+        assert!(!player.is_finished());
+    }
+
+    #[test]
+    fn ping_pong_seek_keeps_direction() {
+        let mut player = player(vec![1, 2, 3, 4], AnimationBehaviour::PingPong);
+        assert_eq!(indices(&mut player, 4), vec![2, 3, 4, 3]);
+        player.seek(2);
+        assert_eq!(indices(&mut player, 2), vec![2, 1]);
+    }
+
+    #[test]
+    fn single_frame() {
+        let mut player = player(vec![7], AnimationBehaviour::RunOnce);
+        assert!(player.tick(FRAME));
+        assert!(player.is_finished());
+        let mut player = self::player(vec![7], AnimationBehaviour::Reverse);
+        assert!(player.tick(FRAME));
+        for behaviour in [AnimationBehaviour::Loop, AnimationBehaviour::PingPong] {
+            let mut player = self::player(vec![7], behaviour);
+            assert_eq!(indices(&mut player, 3), vec![7, 7, 7]);
+            assert!(!player.is_finished());
+        }
+    }
+
+    #[test]
+    fn speed_and_pause() {
+        let mut player = player(vec![1, 2, 3], AnimationBehaviour::Loop).with_speed(2.);
+        player.tick(FRAME);
+        assert_eq!(player.index(), Some(3));
+        player.pause();
+        player.tick(FRAME);
+        assert_eq!(player.index(), Some(3));
+        player.play();
+        player.speed = f32::INFINITY;
+        player.tick(FRAME);
+        player.speed = f32::NAN;
+        player.tick(FRAME);
+        player.speed = -1.;
+        let index = player.index();
+        player.tick(FRAME);
+        assert_eq!(player.index(), index);
+    }
+
+    #[test]
+    fn frame_durations() {
+        let animation = Animation::new(vec![1, 2, 3], AnimationBehaviour::Loop)
+            .with_frame_duration(FRAME)
+            .with_frame_durations(vec![FRAME, FRAME * 3]);
+        assert_eq!(animation.duration(1), FRAME * 3);
+        assert_eq!(animation.duration(2), FRAME);
+        let mut player = AnimationPlayer::new(animation);
+        assert_eq!(indices(&mut player, 6), vec![2, 2, 2, 3, 1, 2]);
+    }
+
+    #[test]
+    fn empty() {
+        let mut player = player(vec![], AnimationBehaviour::RunOnce);
+        assert!(!player.tick(FRAME));
+        assert_eq!(player.index(), None);
+    }
 }
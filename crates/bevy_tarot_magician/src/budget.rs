@@ -0,0 +1,248 @@
+//! Memory budgets for `HandleMap`s
+//!
+//! A `HandleMap` with a `MemoryBudget` drops the handles of assets nobody else holds, least recently used
+//! first, while the loaded assets use more memory than the budget. Dropped keys can simply be loaded again.
+//!
+//! # Example usage:
+//! ```
+//! use std::time::Duration;
+//! use bevy_asset::prelude::*;
+//! use bevy_tarot_magician::animation::{AnimationSet, AnimationSetHandleMap};
+//! use bevy_tarot_magician::budget::MemoryBudget;
+//! use bevy_tarot_magician::AssetKey;
+//! #[derive(Clone, Hash, PartialEq, Eq, Debug)]
+//! struct Key(String);
+//! # impl From<String> for Key { fn from(s: String) -> Self { Key(s) } }
+//! # impl From<Key> for String { fn from(k: Key) -> Self { k.0 } }
+//! impl AssetKey for Key {}
+//!
+//! let mut assets = Assets::<AnimationSet>::default();
+//! let mut handle_map = AnimationSetHandleMap::<Key>::default()
+//!     .with_budget(MemoryBudget::new(0).with_min_idle(Duration::ZERO));
+//! handle_map.insert(Key("hero".into()), assets.add(AnimationSet::default()));
+//! let npc = assets.add(AnimationSet::default());
+//! handle_map.insert(Key("npc".into()), npc.clone());
+//! assert_eq!(handle_map.stats(&assets).in_use, 1);
+//! assert_eq!(handle_map.evict(&assets, Duration::ZERO), vec![Key("hero".into())]);
+//! assert!(handle_map.get(&Key("npc".into())).is_some());
+//! ```
+
+use crate::{AssetKey, HandleMap, TarotAsset};
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_log::*;
+use bevy_time::Time;
+use std::time::Duration;
+
+/// Memory budget of a `HandleMap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryBudget {
+    /// Maximum memory of the loaded assets in bytes.
+    pub bytes: usize,
+    /// Minimum time an asset must be unused before it is evicted.
+    pub min_idle: Duration,
+}
+
+impl MemoryBudget {
+    /// Simpel Constructor
+    pub fn new(bytes: usize) -> Self {
+        Self {
+            bytes,
+            min_idle: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the minimum time an asset must be unused before it is evicted.
+    pub fn with_min_idle(mut self, min_idle: Duration) -> Self {
+        self.min_idle = min_idle;
+        self
+    }
+}
+
+/// Statistics of a `HandleMap`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HandleMapStats {
+    /// Number of stored handles.
+    pub handles: usize,
+    /// Number of loaded assets.
+    pub loaded: usize,
+    /// Number of handles also held outside the map, e.g. by entities.
+    pub in_use: usize,
+    /// Estimated memory of the loaded assets in bytes.
+    pub bytes: usize,
+}
+
+impl<K: AssetKey, A: Asset> HandleMap<K, A> {
+    /// Set the memory budget.
+    pub fn with_budget(mut self, budget: MemoryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set or remove the memory budget.
+    pub fn set_budget(&mut self, budget: Option<MemoryBudget>) {
+        self.budget = budget;
+    }
+
+    /// Memory budget.
+    pub fn budget(&self) -> Option<MemoryBudget> {
+        self.budget
+    }
+
+    /// Check if the handle of `key` is also held outside the map, e.g. by an entity.
+    pub fn in_use(&self, key: &K) -> bool {
+        self.map
+            .get(key)
+            .and_then(|i| self.handles.get(*i))
+            .is_some_and(|(_, h)| match h {
+                Handle::Strong(h) => std::sync::Arc::strong_count(h) > 1,
+                Handle::Weak(_) => false,
+            })
+    }
+
+    /// Time `key` was last in use.
+    pub fn last_used(&self, key: &K) -> Option<Duration> {
+        self.last_used.get(key).copied()
+    }
+
+    /// Mark all keys that are in use (and new keys) as used at `now`.
+    pub fn mark_used(&mut self, now: Duration) {
+        let used = self
+            .map
+            .keys()
+            .filter(|k| self.in_use(k) || !self.last_used.contains_key(*k))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in used {
+            self.last_used.insert(key, now);
+        }
+    }
+}
+
+impl<K: AssetKey, A: TarotAsset> HandleMap<K, A> {
+    /// Estimated memory of the loaded assets in bytes.
+    pub fn memory_usage(&self, assets: &Assets<A>) -> usize {
+        self.handles
+            .iter()
+            .filter_map(|(_, h)| assets.get(h))
+            .map(|a| a.memory_size())
+            .sum()
+    }
+
+    /// Statistics of the stored handles.
+    pub fn stats(&self, assets: &Assets<A>) -> HandleMapStats {
+        HandleMapStats {
+            handles: self.len(),
+            loaded: self
+                .handles
+                .iter()
+                .filter(|(_, h)| assets.contains(h.id()))
+                .count(),
+            in_use: self.map.keys().filter(|k| self.in_use(k)).count(),
+            bytes: self.memory_usage(assets),
+        }
+    }
+
+    /// Remove unused handles of loaded assets, least recently used first, until the memory budget is kept.
+    /// Handles of assets that are still loading are kept. Returns the removed keys.
+    pub fn evict(&mut self, assets: &Assets<A>, now: Duration) -> Vec<K> {
+        let Some(budget) = self.budget else {
+            return vec![];
+        };
+        let mut usage = self.memory_usage(assets);
+        if usage <= budget.bytes {
+            return vec![];
+        }
+        let mut candidates = self
+            .handles
+            .iter()
+            .filter(|(k, h)| assets.contains(h.id()) && !self.in_use(k))
+            .map(|(k, _)| (self.last_used(k).unwrap_or_default(), k.clone()))
+            .filter(|(t, _)| now.saturating_sub(*t) >= budget.min_idle)
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(t, _)| *t);
+        let mut evicted = vec![];
+        for (_, key) in candidates {
+            if usage <= budget.bytes {
+                break;
+            }
+            if let Some(handle) = self.remove(&key) {
+                usage -= assets
+                    .get(&handle)
+                    .map(|a| a.memory_size())
+                    .unwrap_or_default();
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+}
+
+/// Tracks which assets of `HandleMap<K, A>` are in use and evicts unused ones beyond its memory budget.
+pub fn enforce_memory_budget<K: AssetKey, A: TarotAsset>(
+    time: Res<Time>,
+    assets: Res<Assets<A>>,
+    mut handle_map: ResMut<HandleMap<K, A>>,
+) {
+    let now = time.elapsed();
+    handle_map.mark_used(now);
+    let evicted = handle_map.evict(&assets, now);
+    if !evicted.is_empty() {
+        debug!("Evicted {:?} to keep the memory budget", evicted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::AnimationSet;
+    use crate::tests::{key, Key};
+
+    fn handle_map(budget: MemoryBudget) -> HandleMap<Key, AnimationSet> {
+        HandleMap::default().with_budget(budget)
+    }
+
+    #[test]
+    fn evicts_least_recently_used_first() {
+        let mut assets = Assets::<AnimationSet>::default();
+        let size = AnimationSet::default().memory_size();
+        let mut handle_map =
+            handle_map(MemoryBudget::new(size).with_min_idle(Duration::from_secs(1)));
+        handle_map.insert(key("old"), assets.add(AnimationSet::default()));
+        handle_map.mark_used(Duration::ZERO);
+        handle_map.insert(key("new"), assets.add(AnimationSet::default()));
+        handle_map.mark_used(Duration::from_secs(1));
+        assert_eq!(handle_map.memory_usage(&assets), 2 * size);
+        assert_eq!(
+            handle_map.evict(&assets, Duration::from_secs(1)),
+            vec![key("old")]
+        );
+        assert_eq!(handle_map.evict(&assets, Duration::from_secs(5)), vec![]);
+        assert_eq!(handle_map.len(), 1);
+    }
+
+    #[test]
+    fn keeps_used_and_loading_assets() {
+        let mut assets = Assets::<AnimationSet>::default();
+        let mut handle_map = handle_map(MemoryBudget::new(0).with_min_idle(Duration::ZERO));
+        let used = assets.add(AnimationSet::default());
+        handle_map.insert(key("used"), used.clone());
+        handle_map.insert(key("loading"), Handle::weak_from_u128(7));
+        handle_map.insert(key("idle"), assets.add(AnimationSet::default()));
+        let stats = handle_map.stats(&assets);
+        assert_eq!((stats.handles, stats.loaded, stats.in_use), (3, 2, 1));
+        assert_eq!(handle_map.evict(&assets, Duration::ZERO), vec![key("idle")]);
+        assert!(handle_map.get(&key("used")).is_some());
+        assert!(handle_map.get(&key("loading")).is_some());
+    }
+
+    #[test]
+    fn no_budget() {
+        let mut assets = Assets::<AnimationSet>::default();
+        let mut handle_map = HandleMap::<Key, AnimationSet>::default();
+        handle_map.insert(key("idle"), assets.add(AnimationSet::default()));
+        assert!(handle_map
+            .evict(&assets, Duration::from_secs(60))
+            .is_empty());
+    }
+}
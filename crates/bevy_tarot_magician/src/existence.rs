@@ -0,0 +1,105 @@
+//! Cached existence checks for asset paths
+
+use bevy_asset::io::AssetReaderError;
+use bevy_asset::{AssetPath, AssetServer, AssetServerMode};
+use bevy_tasks::{block_on, IoTaskPool};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Cache of which asset paths exist.
+/// Unknown paths are checked through the `AssetReader` of their asset source (respecting processed assets)
+/// on the IO task pool, so no I/O happens on the calling thread.
+/// Without an `IoTaskPool` (e.g. in tests) the check blocks the calling thread instead.
+///
+/// # Example usage:
+/// ```
+/// use bevy_asset::AssetPath;
+/// use bevy_tarot_magician::AssetExistence;
+/// let existence = AssetExistence::default();
+/// let path = AssetPath::from("sprites/hero.ron");
+/// assert_eq!(existence.get(&path), None);
+/// existence.set(path.clone(), false);
+/// assert_eq!(existence.get(&path), Some(false));
+/// existence.forget(&path);
+/// assert_eq!(existence.get(&path), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AssetExistence {
+    /// Result per path, `None` while the check is running.
+    known: Arc<RwLock<HashMap<AssetPath<'static>, Option<bool>>>>,
+}
+
+impl AssetExistence {
+    /// Cached result for `path`.
+    pub fn get(&self, path: &AssetPath<'static>) -> Option<bool> {
+        self.known.read().ok()?.get(path).copied().flatten()
+    }
+
+    /// Cached result for `path`. If it is unknown, a check is started in the background and `None` is returned
+    /// until it finished.
+    pub fn check(&self, path: &AssetPath<'static>, asset_server: &AssetServer) -> Option<bool> {
+        {
+            let mut known = self.known.write().ok()?;
+            if let Some(result) = known.get(path) {
+                return *result;
+            }
+            known.insert(path.clone(), None);
+        }
+        let Some(pool) = IoTaskPool::try_get() else {
+            return Some(block_on(self.check_now(path, asset_server)));
+        };
+        let existence = self.clone();
+        let server = asset_server.clone();
+        let path = path.clone();
+        pool.spawn(async move { existence.check_now(&path, &server).await })
+            .detach();
+        None
+    }
+
+    /// Check `path` regardless of the cached result and cache the new one.
+    pub async fn check_now(&self, path: &AssetPath<'static>, asset_server: &AssetServer) -> bool {
+        let exists = path_exists(asset_server, path).await;
+        self.set(path.clone(), exists);
+        exists
+    }
+
+    /// Set the result for `path`, e.g. after a load failed.
+    pub fn set(&self, path: AssetPath<'static>, exists: bool) {
+        if let Ok(mut known) = self.known.write() {
+            known.insert(path, Some(exists));
+        }
+    }
+
+    /// Forget the result for `path`.
+    pub fn forget(&self, path: &AssetPath<'static>) {
+        if let Ok(mut known) = self.known.write() {
+            known.remove(path);
+        }
+    }
+
+    /// Forget all results, e.g. after files were added.
+    pub fn clear(&self) {
+        if let Ok(mut known) = self.known.write() {
+            known.clear();
+        }
+    }
+}
+
+/// Check if `path` can be read from its asset source. Errors other than "not found" count as existing,
+/// so the asset server reports them when loading.
+async fn path_exists(server: &AssetServer, path: &AssetPath<'static>) -> bool {
+    let Ok(source) = server.get_source(path.source().clone()) else {
+        return false;
+    };
+    let reader = match server.mode() {
+        AssetServerMode::Processed => match source.processed_reader() {
+            Ok(reader) => reader,
+            Err(_) => return false,
+        },
+        AssetServerMode::Unprocessed => source.reader(),
+    };
+    !matches!(
+        reader.read(path.path()).await,
+        Err(AssetReaderError::NotFound(_))
+    )
+}
@@ -0,0 +1,548 @@
+//! Sprite sheets exported from Aseprite and TexturePacker
+//!
+//! Both tools export a JSON file with the frame rectangles as a hash or an array.
+//! `JsonSpriteSheetLoader` loads plain `*.json` files into a `SpriteSheet` with named sprites and adds the
+//! animations as the labeled sub asset `animations`. Which tool exported the file is set in the loader settings:
+//! - `JsonExporter::Aseprite` (default): one animation per frame tag, frame durations are kept.
+//! - `JsonExporter::TexturePacker`: frames named like `run_01.png`, `run_02.png` become the looping animation `run`.
+//!
+//! `load_json_sprite_sheet` and `load_json_animation_set` pass the settings to the `AssetServer`.
+//! Files loaded by path alone can set them in a `.meta` file instead.
+//!
+//! # Example usage:
+//! ```
+//! use bevy_tarot_magician::import::{JsonExporter, JsonSpriteSheetSettings, ANIMATIONS_LABEL};
+//! use bevy_asset::AssetPath;
+//! // `hero.png` with its sprite sheet in `hero.json`
+//! let settings = JsonSpriteSheetSettings::default().with_exporter(JsonExporter::TexturePacker);
+//! assert_eq!(settings.exporter, JsonExporter::TexturePacker);
+//! let animations = AssetPath::from("sprites/hero.json").with_label(ANIMATIONS_LABEL);
+//! assert_eq!(animations.to_string(), "sprites/hero.json#animations");
+//! ```
+
+use crate::animation::{Animation, AnimationBehaviour, AnimationSet, AnimationSetHandleMap};
+use crate::sprite::*;
+use crate::{load_asset_from, AssetPathMap, MagicianError, SpriteAssetKey};
+use bevy_asset::io::Reader;
+use bevy_asset::prelude::*;
+use bevy_asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy_log::*;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Formatter;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Label of the `AnimationSet` in imported sprite sheets.
+pub const ANIMATIONS_LABEL: &str = "animations";
+
+/// File extension of JSON sprite sheets.
+const JSON_EXTENSION: &str = "json";
+
+/// Tool that exported a JSON sprite sheet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JsonExporter {
+    /// Aseprite JSON export
+    #[default]
+    Aseprite,
+    /// TexturePacker JSON (hash or array)
+    TexturePacker,
+}
+
+/// Settings of `JsonSpriteSheetLoader`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonSpriteSheetSettings {
+    /// Tool that exported the file
+    pub exporter: JsonExporter,
+}
+
+impl JsonSpriteSheetSettings {
+    /// Set the exporter.
+    pub fn with_exporter(mut self, exporter: JsonExporter) -> Self {
+        self.exporter = exporter;
+        self
+    }
+}
+
+/// Load Asset wrapper for the `Handle<SpriteSheet>` in `{name}.json` exported by `exporter`
+pub fn load_json_sprite_sheet<K: SpriteAssetKey>(
+    key: K,
+    exporter: JsonExporter,
+    sprite_paths: &SpritePathMap<K>,
+    sprite_sheet_handle_map: &mut SpriteSheetHandleMap<K>,
+    asset_server: &AssetServer,
+) -> Result<Handle<SpriteSheet>, MagicianError> {
+    load_asset_from(
+        key,
+        sprite_paths,
+        sprite_sheet_handle_map,
+        asset_server,
+        JSON_EXTENSION,
+        None,
+        JsonSpriteSheetSettings::default().with_exporter(exporter),
+    )
+}
+
+/// Load Asset wrapper for the `Handle<AnimationSet>` of the sprite sheet in `{name}.json` exported by `exporter`
+pub fn load_json_animation_set<K: SpriteAssetKey>(
+    key: K,
+    exporter: JsonExporter,
+    sprite_paths: &AssetPathMap<K>,
+    animation_set_handle_map: &mut AnimationSetHandleMap<K>,
+    asset_server: &AssetServer,
+) -> Result<Handle<AnimationSet>, MagicianError> {
+    load_asset_from(
+        key,
+        sprite_paths,
+        animation_set_handle_map,
+        asset_server,
+        JSON_EXTENSION,
+        Some(ANIMATIONS_LABEL),
+        JsonSpriteSheetSettings::default().with_exporter(exporter),
+    )
+}
+
+/// AssetLoader for Aseprite and TexturePacker JSON exports
+#[derive(Default)]
+pub struct JsonSpriteSheetLoader {}
+
+/// Loading errors for `JsonSpriteSheetLoader`
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum JsonSpriteSheetLoadingError {
+    /// An [IO](std::io) Error
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [JSON](serde_json) Error
+    #[error("Could not parse JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+impl AssetLoader for JsonSpriteSheetLoader {
+    type Asset = SpriteSheet;
+    type Settings = JsonSpriteSheetSettings;
+    type Error = JsonSpriteSheetLoadingError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let json = serde_json::from_slice::<JsonSheet>(&bytes)?;
+        let animations = match settings.exporter {
+            JsonExporter::Aseprite => json.aseprite_animations(),
+            JsonExporter::TexturePacker => json.texture_packer_animations(),
+        };
+        load_context.add_labeled_asset(ANIMATIONS_LABEL.to_string(), animations);
+        Ok(json.sprite_sheet(load_context.path()))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[JSON_EXTENSION]
+    }
+}
+
+/// JSON export shared by Aseprite and TexturePacker.
+#[derive(Deserialize, Debug)]
+struct JsonSheet {
+    frames: JsonFrames,
+    meta: JsonMeta,
+}
+
+/// Frames as hash (keyed by name, in file order) or array.
+#[derive(Debug)]
+struct JsonFrames(Vec<JsonFrame>);
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct JsonFrame {
+    #[serde(default)]
+    filename: String,
+    frame: JsonRect,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default)]
+    sprite_source_size: Option<JsonRect>,
+    #[serde(default)]
+    source_size: Option<JsonSize>,
+    #[serde(default)]
+    duration: Option<u64>,
+    #[serde(default)]
+    pivot: Option<JsonPivot>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+struct JsonRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+struct JsonSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+struct JsonPivot {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct JsonMeta {
+    size: JsonSize,
+    #[serde(default)]
+    frame_tags: Vec<JsonFrameTag>,
+}
+
+#[derive(Deserialize, Debug)]
+struct JsonFrameTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+    #[serde(default)]
+    repeat: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for JsonFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = JsonFrames;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a hash or an array of frames")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut frames = vec![];
+                while let Some(frame) = seq.next_element::<JsonFrame>()? {
+                    frames.push(frame);
+                }
+                Ok(JsonFrames(frames))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = vec![];
+                while let Some((filename, mut frame)) = map.next_entry::<String, JsonFrame>()? {
+                    frame.filename = filename;
+                    frames.push(frame);
+                }
+                Ok(JsonFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_any(FramesVisitor)
+    }
+}
+
+impl JsonSheet {
+    /// Sprite sheet with a named sprite per frame.
+    fn sprite_sheet(&self, path: &Path) -> SpriteSheet {
+        let mut list = vec![];
+        let mut sprites = BTreeMap::new();
+        for (index, frame) in self.frames.0.iter().enumerate() {
+            let JsonRect { x, y, w, h } = frame.frame;
+            let (w, h) = if frame.rotated {
+                warn!(
+                    "Rotated frame {:?} in {:?} is not supported and will appear rotated.",
+                    frame.filename, path
+                );
+                (h, w)
+            } else {
+                (w, h)
+            };
+            list.push(SpriteData::new(
+                (x, y),
+                (x.saturating_add(w), y.saturating_add(h)),
+            ));
+            let mut meta = SpriteMeta::default();
+            if !frame.filename.is_empty() {
+                meta = meta.with_name(frame_name(&frame.filename));
+            }
+            if let (Some(pivot), Some(source)) = (frame.pivot, frame.source_size) {
+                let trim = frame
+                    .sprite_source_size
+                    .map_or((0., 0.), |r| (r.x as f32, r.y as f32));
+                meta = meta.with_pivot((
+                    pivot.x * source.w as f32 - trim.0,
+                    pivot.y * source.h as f32 - trim.1,
+                ));
+            }
+            for tag in self.meta.frame_tags.iter() {
+                if (tag.from..=tag.to).contains(&index) {
+                    meta = meta.with_tag(tag.name.clone());
+                }
+            }
+            if meta != SpriteMeta::default() {
+                sprites.insert(index as u32, meta);
+            }
+        }
+        let sheet = SpriteSheet {
+            layout: SpriteSheetLayout::List(list),
+            size: (self.meta.size.w, self.meta.size.h),
+            grid_sprite_size: Default::default(),
+            sprites,
+        };
+        sheet.warn_duplicate_names(path);
+        sheet
+    }
+
+    /// One animation per frame tag, or `default` with all frames if there are no tags.
+    fn aseprite_animations(&self) -> AnimationSet {
+        let mut set = AnimationSet::default();
+        if self.frames.0.is_empty() {
+            return set;
+        }
+        if self.meta.frame_tags.is_empty() && self.frames.0.len() > 1 {
+            let frames = (0..self.frames.0.len()).collect::<Vec<_>>();
+            set = set.with_animation(
+                "default",
+                self.timed_animation(frames, AnimationBehaviour::Loop),
+            );
+        }
+        for tag in self.meta.frame_tags.iter() {
+            let to = tag.to.min(self.frames.0.len() - 1);
+            let mut frames = (tag.from..=to).collect::<Vec<_>>();
+            if frames.is_empty() {
+                warn!("Frame tag {:?} has no frames", tag.name);
+                continue;
+            }
+            let once = tag.repeat.as_deref() == Some("1");
+            let behaviour = match (tag.direction.as_str(), once) {
+                ("reverse", true) => AnimationBehaviour::Reverse,
+                ("reverse", false) => {
+                    frames.reverse();
+                    AnimationBehaviour::Loop
+                }
+                ("pingpong", _) => AnimationBehaviour::PingPong,
+                ("pingpong_reverse", _) => {
+                    frames.reverse();
+                    AnimationBehaviour::PingPong
+                }
+                (_, true) => AnimationBehaviour::RunOnce,
+                (_, false) => AnimationBehaviour::Loop,
+            };
+            set = set.with_animation(tag.name.clone(), self.timed_animation(frames, behaviour));
+        }
+        set
+    }
+
+    /// Animation over `frames` with the duration of each frame.
+    fn timed_animation(&self, frames: Vec<usize>, behaviour: AnimationBehaviour) -> Animation {
+        let durations = frames
+            .iter()
+            .map(|i| {
+                let duration = self.frames.0.get(*i).and_then(|f| f.duration);
+                Duration::from_millis(duration.unwrap_or(100).max(1))
+            })
+            .collect();
+        Animation::new(frames, behaviour).with_frame_durations(durations)
+    }
+
+    /// Looping animations of frames named like `run_01`, `run_02`, ...
+    fn texture_packer_animations(&self) -> AnimationSet {
+        let mut groups = BTreeMap::<String, Vec<(u64, usize)>>::new();
+        for (index, frame) in self.frames.0.iter().enumerate() {
+            let name = frame_name(&frame.filename);
+            let base = name.trim_end_matches(|c: char| c.is_ascii_digit());
+            let Ok(number) = name[base.len()..].parse::<u64>() else {
+                continue;
+            };
+            let base = base.trim_end_matches(['_', '-', ' ', '.']);
+            groups
+                .entry(base.to_string())
+                .or_default()
+                .push((number, index));
+        }
+        groups
+            .into_iter()
+            .fold(AnimationSet::default(), |set, (name, mut frames)| {
+                frames.sort();
+                let frames = frames.into_iter().map(|(_, i)| i).collect();
+                set.with_animation(name, Animation::new(frames, AnimationBehaviour::Loop))
+            })
+    }
+}
+
+/// Frame name without the file extension, e.g. `run_01` for `run_01.png`.
+fn frame_name(filename: &str) -> String {
+    Path::new(filename)
+        .with_extension("")
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{app, key, update_until, Key};
+
+    const HASH: &str = r#"{
+        "frames": {
+            "run_02.png": {"frame": {"x": 16, "y": 0, "w": 16, "h": 16}, "duration": 50},
+            "run_01.png": {"frame": {"x": 0, "y": 0, "w": 16, "h": 16}, "duration": 150},
+            "idle.png": {
+                "frame": {"x": 32, "y": 0, "w": 8, "h": 16},
+                "spriteSourceSize": {"x": 4, "y": 0, "w": 8, "h": 16},
+                "sourceSize": {"w": 16, "h": 16},
+                "pivot": {"x": 0.5, "y": 1.0}
+            }
+        },
+        "meta": {"size": {"w": 48, "h": 16}}
+    }"#;
+
+    fn frames(tags: &str, count: usize) -> String {
+        let frames = (0..count)
+            .map(|i| {
+                format!(
+                    r#"{{"filename": "f{i}", "frame": {{"x": {}, "y": 0, "w": 8, "h": 8}}, "duration": 100}}"#,
+                    i * 8
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"{{"frames": [{frames}], "meta": {{"size": {{"w": 64, "h": 8}}, "frameTags": [{tags}]}}}}"#
+        )
+    }
+
+    fn parse(json: &str) -> JsonSheet {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn tag(name: &str, from: usize, to: usize, direction: &str, repeat: Option<&str>) -> String {
+        let repeat = repeat.map_or(String::new(), |r| format!(r#", "repeat": "{r}""#));
+        format!(
+            r#"{{"name": "{name}", "from": {from}, "to": {to}, "direction": "{direction}"{repeat}}}"#
+        )
+    }
+
+    #[test]
+    fn hash_keeps_file_order() {
+        let json = parse(HASH);
+        let sheet = json.sprite_sheet(Path::new("hero.json"));
+        assert_eq!(sheet.len(), 3);
+        assert_eq!(sheet.index_of("run_02"), Some(0));
+        assert_eq!(sheet.index_of("run_01"), Some(1));
+        assert_eq!(sheet.index_of("idle"), Some(2));
+        let idle = sheet.get(2).unwrap();
+        assert_eq!((idle.min, idle.max), ((32, 0), (40, 16)));
+        assert_eq!(sheet.meta(2).unwrap().pivot, Some((4., 16.)));
+        assert_eq!(sheet.meta(0).unwrap().pivot, None);
+    }
+
+    #[test]
+    fn array_frames() {
+        let json = parse(&frames("", 3));
+        let sheet = json.sprite_sheet(Path::new("hero.json"));
+        assert_eq!(sheet.len(), 3);
+        assert_eq!(sheet.index_of("f1"), Some(1));
+        assert_eq!(sheet.size(), (64, 8));
+    }
+
+    #[test]
+    fn texture_packer_animations() {
+        let set = parse(HASH).texture_packer_animations();
+        assert_eq!(set.names().collect::<Vec<_>>(), vec!["run"]);
+        let run = set.get("run").unwrap();
+        assert_eq!(run.key_frames, vec![1, 0]);
+        assert_eq!(run.behaviour, AnimationBehaviour::Loop);
+    }
+
+    #[test]
+    fn aseprite_durations() {
+        let set = parse(HASH).aseprite_animations();
+        let animation = set.get("default").unwrap();
+        assert_eq!(animation.key_frames, vec![0, 1, 2]);
+        assert_eq!(
+            animation.frame_durations,
+            vec![
+                Duration::from_millis(50),
+                Duration::from_millis(150),
+                Duration::from_millis(100)
+            ]
+        );
+    }
+
+    #[test]
+    fn aseprite_tags() {
+        let tags = [
+            tag("forward", 0, 2, "forward", None),
+            tag("once", 0, 2, "forward", Some("1")),
+            tag("reverse", 0, 2, "reverse", None),
+            tag("reverse_once", 0, 2, "reverse", Some("1")),
+            tag("pingpong", 0, 2, "pingpong", None),
+            tag("pingpong_reverse", 0, 2, "pingpong_reverse", None),
+            tag("clamped", 2, 9, "forward", None),
+            tag("empty", 5, 9, "forward", None),
+        ]
+        .join(",");
+        let set = parse(&frames(&tags, 3)).aseprite_animations();
+        let check = |name: &str, key_frames: Vec<usize>, behaviour: AnimationBehaviour| {
+            let animation = set.get(name).unwrap();
+            assert_eq!(animation.key_frames, key_frames, "{name}");
+            assert_eq!(animation.behaviour, behaviour, "{name}");
+        };
+        check("forward", vec![0, 1, 2], AnimationBehaviour::Loop);
+        check("once", vec![0, 1, 2], AnimationBehaviour::RunOnce);
+        check("reverse", vec![2, 1, 0], AnimationBehaviour::Loop);
+        check("reverse_once", vec![0, 1, 2], AnimationBehaviour::Reverse);
+        check("pingpong", vec![0, 1, 2], AnimationBehaviour::PingPong);
+        check(
+            "pingpong_reverse",
+            vec![2, 1, 0],
+            AnimationBehaviour::PingPong,
+        );
+        check("clamped", vec![2], AnimationBehaviour::Loop);
+        assert!(set.get("empty").is_none());
+        assert!(set.get("default").is_none());
+    }
+
+    #[test]
+    fn no_frames() {
+        let json = parse(&frames(&tag("idle", 0, 0, "forward", None), 0));
+        assert!(json.aseprite_animations().animations.is_empty());
+        assert!(json.texture_packer_animations().animations.is_empty());
+        assert!(json.sprite_sheet(Path::new("empty.json")).is_empty());
+    }
+
+    #[test]
+    fn plain_json_with_settings() {
+        let mut app = app();
+        let server = app.world().resource::<AssetServer>().clone();
+        let mut paths = AssetPathMap::default();
+        paths.insert(key("hero"), "hero.png".to_string());
+        let mut sheets = SpriteSheetHandleMap::<Key>::default();
+        let mut animation_sets = AnimationSetHandleMap::<Key>::default();
+        let exporter = JsonExporter::TexturePacker;
+        let sheet = load_json_sprite_sheet(key("hero"), exporter, &paths, &mut sheets, &server);
+        let set =
+            load_json_animation_set(key("hero"), exporter, &paths, &mut animation_sets, &server);
+        let (sheet, set) = (sheet.unwrap(), set.unwrap());
+        update_until(&mut app, |world| {
+            world.resource::<Assets<SpriteSheet>>().contains(&sheet)
+                && world.resource::<Assets<AnimationSet>>().contains(&set)
+        });
+        let world = app.world();
+        let sheet = world.resource::<Assets<SpriteSheet>>().get(&sheet).unwrap();
+        assert_eq!(sheet.index_of("run_02"), Some(1));
+        let set = world.resource::<Assets<AnimationSet>>().get(&set).unwrap();
+        assert_eq!(set.names().collect::<Vec<_>>(), vec!["run"]);
+    }
+}
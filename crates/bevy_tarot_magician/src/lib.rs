@@ -1,33 +1,90 @@
 #![warn(missing_docs)]
 //! Asset management
+//!
+//! `HandleMap`s store the handles of assets by `AssetKey`, `AssetPathMap` stores their paths.
+//!
+//! # Example usage:
+//! ```
+//! use bevy_asset::prelude::*;
+//! use bevy_tarot_magician::animation::{AnimationSet, AnimationSetHandleMap};
+//! use bevy_tarot_magician::AssetKey;
+//! #[derive(Clone, Hash, PartialEq, Eq, Debug)]
+//! struct Key(String);
+//! # impl From<String> for Key { fn from(s: String) -> Self { Key(s) } }
+//! # impl From<Key> for String { fn from(k: Key) -> Self { k.0 } }
+//! impl AssetKey for Key {}
+//!
+//! let mut assets = Assets::<AnimationSet>::default();
+//! let mut handle_map = AnimationSetHandleMap::<Key>::default();
+//! let handle = assets.add(AnimationSet::default());
+//! handle_map.insert(Key("hero".into()), handle.clone());
+//! assert_eq!(handle_map.get_key(&handle.id()), Some(&Key("hero".into())));
+//! assert_eq!(handle_map.remove(&Key("hero".into())), Some(handle));
+//! assert!(handle_map.is_empty());
+//! ```
 
 use serde::{Deserialize, Serialize};
 use sprite::*;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 use bevy_app::prelude::*;
+use bevy_asset::io::AssetReaderError;
 use bevy_asset::prelude::*;
+use bevy_asset::meta::Settings;
+use bevy_asset::{AssetLoadError, AssetLoadFailedEvent, AssetPath, LoadState};
 use bevy_ecs::prelude::*;
 use bevy_log::*;
 use bevy_tarot_hermit::{HermitError, SimpleToString};
 
-mod animation;
+pub mod animation;
+pub mod budget;
+mod existence;
+pub mod import;
+pub mod manifest;
+pub mod progress;
 pub mod sprite;
 pub use sprite::SpriteAssetKey; // TODO: Prelude
+pub use existence::AssetExistence;
 pub use bevy_asset::AssetServer;
 
 /// Assets plugin
 pub fn plugin<K : SpriteAssetKey>(app: &mut App) {
     app.init_asset::<SpriteSheet>();
     app.init_asset_loader::<SpriteSheetLoader>();
+    app.init_asset::<animation::AnimationSet>();
+    app.init_asset_loader::<animation::AnimationSetLoader>();
+    app.init_asset_loader::<import::JsonSpriteSheetLoader>();
     app.insert_resource(SpriteHandleMap::<K>::default());
     app.insert_resource(SpriteSheetHandleMap::<K>::default());
     app.insert_resource(TextureAtlasLayoutHandleMap::<K>::default());
+    app.insert_resource(animation::AnimationSetHandleMap::<K>::default());
     app.insert_resource(SpritePathMap::<K>::default());
+    app.init_asset::<manifest::AssetManifest>();
+    app.init_asset_loader::<manifest::AssetManifestLoader>();
+    app.insert_resource(manifest::AssetManifests::<K>::default());
+    app.insert_resource(manifest::AssetGroups::<K>::default());
+    app.add_systems(Update, manifest::apply_asset_manifests::<K>);
+    app.add_systems(
+        Update,
+        (
+            track_missing_assets::<K, bevy_render::texture::Image>,
+            track_missing_assets::<K, SpriteSheet>,
+            track_missing_assets::<K, animation::AnimationSet>,
+            budget::enforce_memory_budget::<K, bevy_render::texture::Image>,
+            budget::enforce_memory_budget::<K, SpriteSheet>,
+            budget::enforce_memory_budget::<K, animation::AnimationSet>,
+        ),
+    );
     app.observe(add_sprite_to_entity::<K>);
+    app.add_systems(Update, skip_empty_grid_cells::<K>);
+    app.add_systems(
+        Update,
+        (animation::play_animations, update_sprite_anchors).chain(),
+    );
 }
 
 /// Errors created by the Magician Crate.
@@ -39,9 +96,15 @@ pub enum MagicianError {
     /// Asset was not found but expected to be loaded.
     #[error("Could not find sprite handle for {0:?}")]
     AssetNotFound(String),
+    /// Sprite sheet has no sprite with this name (name, key)
+    #[error("Sprite {0:?} not found in sprite sheet of {1:?}")]
+    SpriteNotFound(String, String),
     /// Entity was not found.
     #[error("Entity {0:?} not found.")]
     EntityNotFound(Entity),
+    /// Key in a manifest could not be converted into an `AssetKey` (key, manifest)
+    #[error("Invalid asset key {0:?} in {1}")]
+    InvalidKey(String, String),
     /// Generic error
     #[error("<Hermit Error> {0}")]
     HermitError(HermitError),
@@ -50,10 +113,19 @@ pub enum MagicianError {
 /// Trait to mark Assets in this crate.
 pub trait TarotAsset: Asset + Debug {
     /// Assets have an associated file extension.
-    /// TODO: it would be nice to also differentiate between {name}.ron and {name}_anim.ron
     fn file_extension() -> Option<&'static str> {
         None
     }
+
+    /// Suffix appended to the file name, e.g. `_anim` for `{name}_anim.ron`.
+    fn file_suffix() -> Option<&'static str> {
+        None
+    }
+
+    /// Estimated memory used by the asset in bytes.
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
 }
 
 /// Load assets and discard errors.
@@ -67,11 +139,10 @@ pub fn load_assets_unchecked<K: AssetKey, T: TarotAsset>(
     load_assets(keys, paths, handle_map, asset_server)
         .into_iter()
         .filter_map(|r| {
-            r.map_err(|e| {
+            r.inspect_err(|e| {
                 if print_warnings {
-                    warn!("{:?}", &e);
+                    warn!("{:?}", e);
                 }
-                e
             })
                 .ok()
         })
@@ -91,40 +162,125 @@ pub fn load_assets<K: AssetKey, T: TarotAsset>(
 }
 
 /// Load asset
+///
+/// Existence is checked through the asset source of the path (see `AssetExistence`).
+/// While the check is still running the asset is loaded anyway. A missing file then fails to load,
+/// its handle is dropped by `track_missing_assets` and later calls return `AssetNotFound`.
 pub fn load_asset<K: AssetKey, T: TarotAsset>(
     key: K,
     paths: &AssetPathMap<K>,
     handle_map: &mut HandleMap<K, T>,
     asset_server: &AssetServer,
 ) -> Result<Handle<T>, MagicianError> {
-    let path = paths
-        .get(&key)
-        .map(|p| {
-            if let Some(file_ext) = T::file_extension() {
-                let mut path = PathBuf::from(p);
-                path.set_extension(file_ext);
-                path
-            } else {
-                PathBuf::from(p)
-            }
-        })
-        .ok_or(MagicianError::AssetNotFound(format!(
-            "{:?} [No path saved]",
-            key
-        )))?;
-    {
-        // TODO: Seems expensive ...
-        let mut p = PathBuf::from("assets");
-        p.push(path.as_path());
-        if !p.exists() {
-            return Err(MagicianError::AssetNotFound(format!("{:?}", key)));
+    let path = asset_path(&key, paths, T::file_suffix(), T::file_extension());
+    load_asset_path(key, path, handle_map, asset_server, |server, path| {
+        server.load(path)
+    })
+}
+
+/// Load asset from the file with `file_extension` instead of the one of `T` with the loader settings `settings`,
+/// optionally as the labeled sub asset `label` of that file.
+pub fn load_asset_from<K: AssetKey, T: TarotAsset, S: Settings + Clone>(
+    key: K,
+    paths: &AssetPathMap<K>,
+    handle_map: &mut HandleMap<K, T>,
+    asset_server: &AssetServer,
+    file_extension: &str,
+    label: Option<&str>,
+    settings: S,
+) -> Result<Handle<T>, MagicianError> {
+    let path = asset_path(&key, paths, None, Some(file_extension));
+    let path = match label {
+        Some(label) => path.map(|p| p.with_label(label.to_string())),
+        None => path,
+    };
+    load_asset_path(key, path, handle_map, asset_server, |server, path| {
+        server.load_with_settings(path, move |s: &mut S| *s = settings.clone())
+    })
+}
+
+/// Load the asset at `path` with `load` if it is not known to be missing.
+fn load_asset_path<K: AssetKey, T: TarotAsset>(
+    key: K,
+    path: Option<AssetPath<'static>>,
+    handle_map: &mut HandleMap<K, T>,
+    asset_server: &AssetServer,
+    load: impl FnOnce(&AssetServer, AssetPath<'static>) -> Handle<T>,
+) -> Result<Handle<T>, MagicianError> {
+    let path = path.ok_or(MagicianError::AssetNotFound(format!(
+        "{:?} [No path saved]",
+        key
+    )))?;
+    let file = path.without_label().into_owned();
+    if let Some(handle) = handle_map.get(&key) {
+        if !is_missing(asset_server.get_load_state(&handle)) {
+            return Ok(handle);
         }
+        handle_map.remove(&key);
+        handle_map.existence.set(file, false);
+        return Err(MagicianError::AssetNotFound(format!("{:?}", key)));
     }
-    let handle: Handle<T> = asset_server.load(path);
+    if handle_map.existence.check(&file, asset_server) == Some(false) {
+        return Err(MagicianError::AssetNotFound(format!("{:?}", key)));
+    }
+    let handle = load(asset_server, path);
     handle_map.insert(key, handle.clone());
     Ok(handle)
 }
 
+/// Asset path of `key` with `suffix` and `file_extension`.
+fn asset_path<K: AssetKey>(
+    key: &K,
+    paths: &AssetPathMap<K>,
+    suffix: Option<&str>,
+    file_extension: Option<&str>,
+) -> Option<AssetPath<'static>> {
+    paths.get(key).map(|p| {
+        let mut path = PathBuf::from(p);
+        if let Some(suffix) = suffix {
+            let mut name = path.file_stem().unwrap_or_default().to_os_string();
+            name.push(suffix);
+            if let Some(ext) = path.extension() {
+                name.push(".");
+                name.push(ext);
+            }
+            path.set_file_name(name);
+        }
+        if let Some(file_ext) = file_extension {
+            path.set_extension(file_ext);
+        }
+        // Parsed from a string to keep the `source://` prefix.
+        AssetPath::from(path.to_string_lossy().into_owned())
+    })
+}
+
+/// Load state of an asset whose file is missing.
+fn is_missing(state: Option<LoadState>) -> bool {
+    matches!(
+        state,
+        Some(LoadState::Failed(error))
+            if matches!(*error, AssetLoadError::AssetReaderError(AssetReaderError::NotFound(_)))
+    )
+}
+
+/// Remembers assets of `handle_map` that failed to load because their file is missing
+/// and removes their keys, so the map only holds assets that can still be loaded.
+pub fn track_missing_assets<K: AssetKey, T: TarotAsset>(
+    mut events: EventReader<AssetLoadFailedEvent<T>>,
+    mut handle_map: ResMut<HandleMap<K, T>>,
+) {
+    for event in events.read() {
+        if let AssetLoadError::AssetReaderError(AssetReaderError::NotFound(_)) = event.error {
+            handle_map
+                .existence
+                .set(event.path.without_label().into_owned(), false);
+            while let Some(key) = handle_map.get_key(&event.id).cloned() {
+                handle_map.remove(&key);
+            }
+        }
+    }
+}
+
 /// AssetKey
 pub trait AssetKey: Sized + Clone + Hash + Eq + Debug + Send + Sync + TryFrom<String> + Into<String> + 'static {
     /// If the path is stored inside the asset key return it.
@@ -153,17 +309,33 @@ impl<T: AssetKey> AssetPathMap<T> {
             self.0.get(key)
         }
     }
+
+    /// Set the path of `key`.
+    pub fn insert(&mut self, key: T, path: String) -> Option<String> {
+        self.0.insert(key, path)
+    }
+
+    /// Remove the path of `key`.
+    pub fn remove(&mut self, key: &T) -> Option<String> {
+        self.0.remove(key)
+    }
 }
 
 /// Map that stores Handles for `AssetKey`s
 #[derive(Resource, Debug)]
 pub struct HandleMap<K: AssetKey, A: Asset> {
-    /// List of Handles
-    handles: Vec<Handle<A>>,
+    /// List of Handles with their `AssetKey`
+    handles: Vec<(K, Handle<A>)>,
     /// Map from `AssetKey` to index of `handles`
     map: HashMap<K, usize>,
     /// Map from (loading/loaded) `AssetId` to `AssetKey`
     id_to_key: HashMap<AssetId<A>, K>,
+    /// Cached existence of asset paths
+    existence: AssetExistence,
+    /// Time each key was last in use
+    last_used: HashMap<K, Duration>,
+    /// Memory budget for the loaded assets
+    budget: Option<budget::MemoryBudget>,
 }
 
 impl<K: AssetKey, A: Asset> Default for HandleMap<K, A> {
@@ -172,6 +344,9 @@ impl<K: AssetKey, A: Asset> Default for HandleMap<K, A> {
             handles: vec![],
             map: HashMap::new(),
             id_to_key: HashMap::new(),
+            existence: AssetExistence::default(),
+            last_used: HashMap::new(),
+            budget: None,
         }
     }
 }
@@ -183,6 +358,11 @@ impl<K: AssetKey + Hash + Eq, A: Asset> HandleMap<K, A> {
         self.map.is_empty()
     }
 
+    /// Number of stored handles.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
     /// Get associated `AssetKey` for a given `AssetId`
     pub fn get_key(&self, id: &AssetId<A>) -> Option<&K> {
         self.id_to_key.get(id)
@@ -192,7 +372,8 @@ impl<K: AssetKey + Hash + Eq, A: Asset> HandleMap<K, A> {
     pub fn get(&self, key: &K) -> Option<Handle<A>> {
         self.map
             .get(key)
-            .and_then(|i| self.handles.get(*i).cloned())
+            .and_then(|i| self.handles.get(*i))
+            .map(|(_, h)| h.clone())
     }
 
     /// Insert new key, handle and id.
@@ -201,35 +382,269 @@ impl<K: AssetKey + Hash + Eq, A: Asset> HandleMap<K, A> {
             return;
         }
         self.id_to_key.insert(handle.id(), key.clone());
-        self.map.insert(key, self.handles.len());
-        self.handles.push(handle);
+        self.map.insert(key.clone(), self.handles.len());
+        self.handles.push((key, handle));
+    }
+
+    /// Remove `key` and return its handle. The asset is unloaded once no other handle is left.
+    pub fn remove(&mut self, key: &K) -> Option<Handle<A>> {
+        let index = self.map.remove(key)?;
+        self.last_used.remove(key);
+        let (_, handle) = self.handles.swap_remove(index);
+        if let Some((moved_key, _)) = self.handles.get(index) {
+            self.map.insert(moved_key.clone(), index);
+        }
+        if self.id_to_key.get(&handle.id()) == Some(key) {
+            // Another key may use the same asset.
+            match self.handles.iter().find(|(_, h)| h.id() == handle.id()) {
+                Some((other, _)) => self.id_to_key.insert(handle.id(), other.clone()),
+                None => self.id_to_key.remove(&handle.id()),
+            };
+        }
+        Some(handle)
+    }
+
+    /// Remove all keys of `group` and return their handles.
+    pub fn remove_group(
+        &mut self,
+        group: &str,
+        groups: &manifest::AssetGroups<K>,
+    ) -> Vec<Handle<A>> {
+        groups
+            .get(group)
+            .map(|keys| keys.iter().filter_map(|k| self.remove(k)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Remove all keys and handles.
+    pub fn clear(&mut self) {
+        self.handles.clear();
+        self.map.clear();
+        self.id_to_key.clear();
+        self.last_used.clear();
     }
 }
 
 impl<K: AssetKey, A: Asset> HandleMap<K, A> {
+    /// Cached existence of the asset paths loaded through this map.
+    pub fn existence(&self) -> &AssetExistence {
+        &self.existence
+    }
+
     /// Check if all assets in the handle map are loaded.
     pub fn all_loaded(&self, asset_server: &AssetServer) -> bool {
         self.map
             .values()
             .filter_map(|i| self.handles.get(*i))
-            .all(|x| asset_server.is_loaded_with_dependencies(x))
+            .all(|(_, h)| asset_server.is_loaded_with_dependencies(h))
     }
 }
 
-/// TODO: Whats up with this?
+/// Path of the file next to the asset of `key` with the extension `file_ending`, if that file exists.
+/// Existence is checked through `existence`, so `None` is also returned while the check is still running.
 pub fn get_associated_file<K: AssetKey>(
     key: &K,
     sprite_paths: &AssetPathMap<K>,
     file_ending: &str,
-) -> Option<PathBuf> {
-    sprite_paths.get(key).and_then(|p| {
-        let path = Path::new(p);
-        if path.exists() {
-            let mut path_buf = path.to_path_buf();
-            path_buf.set_extension(file_ending);
-            Some(path_buf)
-        } else {
-            None
+    existence: &AssetExistence,
+    asset_server: &AssetServer,
+) -> Option<AssetPath<'static>> {
+    asset_path(key, sprite_paths, None, Some(file_ending))
+        .filter(|path| existence.check(path, asset_server) == Some(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_asset::io::memory::{Dir, MemoryAssetReader};
+    use bevy_asset::io::{AssetSource, AssetSourceId};
+    use bevy_asset::AssetPlugin;
+    use bevy_tasks::{block_on, IoTaskPool, TaskPool};
+    use std::path::Path;
+
+    #[derive(Clone, Hash, PartialEq, Eq, Debug)]
+    pub(crate) struct Key(String);
+
+    impl From<String> for Key {
+        fn from(value: String) -> Self {
+            Key(value)
         }
-    })
+    }
+
+    impl From<Key> for String {
+        fn from(value: Key) -> Self {
+            value.0
+        }
+    }
+
+    impl AssetKey for Key {}
+
+    impl SpriteAssetKey for Key {}
+
+    pub(crate) fn key(name: &str) -> Key {
+        Key(name.to_string())
+    }
+
+    /// App with sprite sheet fixtures in memory: `hero.ron` and `sheet.ron` are valid, `broken.ron` is not.
+    /// `hero.json` is a TexturePacker export with the frames `run_01` and `run_02`.
+    pub(crate) fn app() -> App {
+        IoTaskPool::get_or_init(TaskPool::new);
+        let dir = Dir::default();
+        let sheet = "(layout: (rows: 1, cols: 2), size: (32, 16))";
+        dir.insert_asset_text(Path::new("hero.ron"), sheet);
+        dir.insert_asset_text(Path::new("sheet.ron"), sheet);
+        dir.insert_asset_text(Path::new("broken.ron"), "(layout: (rows: 1");
+        dir.insert_asset_text(
+            Path::new("hero.json"),
+            r#"{
+                "frames": {
+                    "run_01.png": {"frame": {"x": 0, "y": 0, "w": 16, "h": 16}},
+                    "run_02.png": {"frame": {"x": 16, "y": 0, "w": 16, "h": 16}}
+                },
+                "meta": {"size": {"w": 32, "h": 16}}
+            }"#,
+        );
+        let mut app = App::new();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        );
+        app.add_plugins(AssetPlugin::default());
+        app.init_asset::<SpriteSheet>();
+        app.init_asset_loader::<SpriteSheetLoader>();
+        app.init_asset::<animation::AnimationSet>();
+        app.init_asset_loader::<import::JsonSpriteSheetLoader>();
+        app
+    }
+
+    /// Update `app` until `done` returns true. The loads run on the IO task pool, so this may take some updates.
+    pub(crate) fn update_until(app: &mut App, mut done: impl FnMut(&World) -> bool) {
+        for _ in 0..10_000 {
+            app.update();
+            if done(app.world()) {
+                return;
+            }
+        }
+        panic!("assets did not finish loading");
+    }
+
+    #[test]
+    fn remove_keeps_other_keys() {
+        let w = Handle::<SpriteSheet>::weak_from_u128(1);
+        let x = Handle::<SpriteSheet>::weak_from_u128(2);
+        let mut handle_map = HandleMap::<Key, SpriteSheet>::default();
+        handle_map.insert(key("a"), w.clone());
+        handle_map.insert(key("c"), x.clone());
+        handle_map.insert(key("b"), x.clone());
+        assert_eq!(handle_map.remove(&key("a")), Some(w.clone()));
+        assert_eq!(handle_map.get(&key("c")), Some(x.clone()));
+        assert_eq!(handle_map.get(&key("b")), Some(x.clone()));
+        assert_eq!(handle_map.remove(&key("b")), Some(x.clone()));
+        assert_eq!(handle_map.get(&key("c")), Some(x.clone()));
+        assert_eq!(handle_map.get_key(&x.id()), Some(&key("c")));
+        assert_eq!(handle_map.get_key(&w.id()), None);
+        assert_eq!(handle_map.remove(&key("b")), None);
+        assert_eq!(handle_map.len(), 1);
+        assert_eq!(handle_map.remove(&key("c")), Some(x.clone()));
+        assert_eq!(handle_map.get_key(&x.id()), None);
+        assert!(handle_map.is_empty());
+    }
+
+    #[test]
+    fn missing_files_are_not_loaded() {
+        let app = app();
+        let server = app.world().resource::<AssetServer>();
+        let mut paths = AssetPathMap::default();
+        paths.insert(key("missing"), "missing.png".to_string());
+        let mut handle_map = HandleMap::<Key, SpriteSheet>::default();
+        let file = AssetPath::from("missing.ron");
+        assert!(!block_on(handle_map.existence().check_now(&file, server)));
+        assert!(matches!(
+            load_asset(key("missing"), &paths, &mut handle_map, server),
+            Err(MagicianError::AssetNotFound(_))
+        ));
+        assert!(handle_map.is_empty());
+        assert_eq!(handle_map.existence().get(&file), Some(false));
+    }
+
+    #[test]
+    fn failed_loads_of_missing_files_are_removed() {
+        let mut app = app();
+        app.insert_resource(HandleMap::<Key, SpriteSheet>::default());
+        app.add_systems(Update, track_missing_assets::<Key, SpriteSheet>);
+        let handle = Handle::<SpriteSheet>::weak_from_u128(1);
+        let mut handle_map = app
+            .world_mut()
+            .resource_mut::<HandleMap<Key, SpriteSheet>>();
+        handle_map.insert(key("a"), handle.clone());
+        handle_map.insert(key("b"), handle.clone());
+        app.world_mut().send_event(AssetLoadFailedEvent {
+            id: handle.id(),
+            path: AssetPath::from("a.ron"),
+            error: AssetLoadError::AssetReaderError(AssetReaderError::NotFound("a.ron".into())),
+        });
+        app.update();
+        let handle_map = app.world().resource::<HandleMap<Key, SpriteSheet>>();
+        assert!(handle_map.is_empty());
+        assert_eq!(handle_map.get_key(&handle.id()), None);
+        assert_eq!(
+            handle_map.existence().get(&AssetPath::from("a.ron")),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn missing_files_are_removed_after_loading() {
+        let mut app = app();
+        app.insert_resource(HandleMap::<Key, SpriteSheet>::default());
+        app.add_systems(Update, track_missing_assets::<Key, SpriteSheet>);
+        let server = app.world().resource::<AssetServer>().clone();
+        let mut paths = AssetPathMap::default();
+        paths.insert(key("missing"), "missing.png".to_string());
+        let mut handle_map = app
+            .world_mut()
+            .resource_mut::<HandleMap<Key, SpriteSheet>>();
+        // Loading starts right away, the file turns out to be missing later.
+        assert!(load_asset(key("missing"), &paths, &mut handle_map, &server).is_ok());
+        update_until(&mut app, |world| {
+            world.resource::<HandleMap<Key, SpriteSheet>>().is_empty()
+        });
+        let mut handle_map = app
+            .world_mut()
+            .resource_mut::<HandleMap<Key, SpriteSheet>>();
+        assert!(matches!(
+            load_asset(key("missing"), &paths, &mut handle_map, &server),
+            Err(MagicianError::AssetNotFound(_))
+        ));
+        assert!(handle_map.is_empty());
+    }
+
+    #[test]
+    fn existing_files_are_loaded() {
+        let mut app = app();
+        let server = app.world().resource::<AssetServer>().clone();
+        let mut paths = AssetPathMap::default();
+        paths.insert(key("sheet"), "sheet.png".to_string());
+        let mut handle_map = HandleMap::<Key, SpriteSheet>::default();
+        let handle = load_asset(key("sheet"), &paths, &mut handle_map, &server).unwrap();
+        assert_eq!(handle_map.get(&key("sheet")), Some(handle.clone()));
+        assert_eq!(handle_map.len(), 1);
+        update_until(&mut app, |world| {
+            world.resource::<Assets<SpriteSheet>>().contains(&handle)
+        });
+        assert!(handle_map.all_loaded(&server));
+        let file = AssetPath::from("sheet.ron");
+        assert!(block_on(handle_map.existence().check_now(&file, &server)));
+        assert_eq!(
+            get_associated_file(
+                &key("sheet"),
+                &paths,
+                "ron",
+                handle_map.existence(),
+                &server
+            ),
+            Some(file)
+        );
+    }
 }
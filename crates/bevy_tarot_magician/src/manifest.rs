@@ -0,0 +1,445 @@
+//! Asset manifests that fill `AssetPathMap`
+//!
+//! A manifest is a `*.manifest.ron` file with key -> path entries, optionally grouped:
+//! `(entries: {"player": "sprites/player.png"}, groups: {"forest": {"tree": "sprites/tree.png"}})`.
+//! Manifests loaded with `AssetManifests::load` are applied whenever they are (re)loaded,
+//! so they hot-reload when the asset server watches for changes.
+//! If several manifests define the same key, the last applied one owns it and a warning is logged.
+//! Removing the owner falls back to the entry of the most recently applied manifest that still defines the key.
+//!
+//! # Example usage:
+//! ```
+//! use bevy_asset::prelude::*;
+//! use bevy_tarot_magician::manifest::{AssetGroups, AssetManifest, AssetManifests};
+//! use bevy_tarot_magician::{AssetKey, AssetPathMap};
+//! #[derive(Clone, Hash, PartialEq, Eq, Debug)]
+//! struct Key(String);
+//! # impl From<String> for Key { fn from(s: String) -> Self { Key(s) } }
+//! # impl From<Key> for String { fn from(k: Key) -> Self { k.0 } }
+//! impl AssetKey for Key {}
+//!
+//! let manifest: AssetManifest = ron::de::from_str(
+//!     r#"(entries: {"player": "sprites/player.png"}, groups: {"forest": {"tree": "sprites/tree.png"}})"#,
+//! ).unwrap();
+//! let handle = Handle::<AssetManifest>::weak_from_u128(1);
+//! let mut manifests = AssetManifests::<Key>::default();
+//! let mut paths = AssetPathMap::default();
+//! let mut groups = AssetGroups::default();
+//! assert!(manifests.apply(handle.id(), &manifest, "base", &mut paths, &mut groups).is_empty());
+//! assert_eq!(paths.get(&Key("tree".into())), Some(&"sprites/tree.png".to_string()));
+//! assert!(groups.get("forest").unwrap().contains(&Key("tree".into())));
+//! manifests.unload(&handle, &mut paths, &mut groups);
+//! assert_eq!(paths.get(&Key("player".into())), None);
+//! ```
+
+use crate::{AssetKey, AssetPathMap, MagicianError};
+use bevy_asset::io::Reader;
+use bevy_asset::prelude::*;
+use bevy_asset::{ron, AssetLoader, AsyncReadExt, LoadContext};
+use bevy_ecs::prelude::*;
+use bevy_log::*;
+use bevy_reflect::TypePath;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use thiserror::Error;
+
+/// Key -> path entries, optionally grouped.
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AssetManifest {
+    /// Entries without a group
+    #[serde(default)]
+    pub entries: BTreeMap<String, String>,
+    /// Entries per group
+    #[serde(default)]
+    pub groups: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl AssetManifest {
+    /// Iterate over all entries as (group, key, path).
+    pub fn iter(&self) -> impl Iterator<Item = (Option<&String>, &String, &String)> {
+        self.entries.iter().map(|(k, p)| (None, k, p)).chain(
+            self.groups
+                .iter()
+                .flat_map(|(g, e)| e.iter().map(move |(k, p)| (Some(g), k, p))),
+        )
+    }
+}
+
+/// Keys of grouped manifest entries, e.g. to unload a group together.
+#[derive(Resource, Debug)]
+pub struct AssetGroups<K: AssetKey> {
+    /// Keys per group
+    groups: HashMap<String, HashSet<K>>,
+}
+
+impl<K: AssetKey> Default for AssetGroups<K> {
+    fn default() -> Self {
+        Self {
+            groups: HashMap::new(),
+        }
+    }
+}
+
+impl<K: AssetKey> AssetGroups<K> {
+    /// Keys in `group`.
+    pub fn get(&self, group: &str) -> Option<&HashSet<K>> {
+        self.groups.get(group)
+    }
+
+    /// Add `key` to `group`.
+    pub fn insert(&mut self, group: impl Into<String>, key: K) {
+        self.groups.entry(group.into()).or_default().insert(key);
+    }
+
+    /// Remove `key` from all groups.
+    pub fn remove_key(&mut self, key: &K) {
+        self.groups.values_mut().for_each(|g| {
+            g.remove(key);
+        });
+        self.groups.retain(|_, g| !g.is_empty());
+    }
+
+    /// Names of all groups.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.groups.keys()
+    }
+}
+
+/// Applied manifest entry (group, key, path).
+type ManifestEntry<K> = (Option<String>, K, String);
+
+/// Loaded manifests and the keys they added to `AssetPathMap<K>`.
+#[derive(Resource, Debug)]
+pub struct AssetManifests<K: AssetKey> {
+    /// Handles of loaded manifests
+    handles: Vec<Handle<AssetManifest>>,
+    /// Entries (group, key, path) added by each manifest, in the order the manifests were applied
+    applied: Vec<(AssetId<AssetManifest>, Vec<ManifestEntry<K>>)>,
+    /// Manifest whose entry is used for each key
+    owners: HashMap<K, AssetId<AssetManifest>>,
+    /// Errors of the last application of each manifest
+    errors: HashMap<AssetId<AssetManifest>, Vec<MagicianError>>,
+}
+
+impl<K: AssetKey> Default for AssetManifests<K> {
+    fn default() -> Self {
+        Self {
+            handles: vec![],
+            applied: vec![],
+            owners: HashMap::new(),
+            errors: HashMap::new(),
+        }
+    }
+}
+
+impl<K: AssetKey> AssetManifests<K> {
+    /// Start loading the manifest at `path`. It is applied once it is loaded.
+    pub fn load(
+        &mut self,
+        path: impl Into<String>,
+        asset_server: &AssetServer,
+    ) -> Handle<AssetManifest> {
+        let handle = asset_server.load::<AssetManifest>(path.into());
+        if !self.handles.contains(&handle) {
+            self.handles.push(handle.clone());
+        }
+        handle
+    }
+
+    /// Errors of the last application of `manifest`.
+    pub fn errors(&self, manifest: &Handle<AssetManifest>) -> &[MagicianError] {
+        self.errors
+            .get(&manifest.id())
+            .map(|e| e.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Errors of all manifests.
+    pub fn all_errors(&self) -> impl Iterator<Item = &MagicianError> {
+        self.errors.values().flatten()
+    }
+
+    /// Remove the entries of `manifest` and forget its handle.
+    pub fn unload(
+        &mut self,
+        manifest: &Handle<AssetManifest>,
+        paths: &mut AssetPathMap<K>,
+        groups: &mut AssetGroups<K>,
+    ) {
+        self.handles.retain(|h| h != manifest);
+        self.errors.remove(&manifest.id());
+        self.remove_entries(manifest.id(), paths, groups);
+    }
+
+    /// Manifest whose entry is used for `key`.
+    pub fn owner(&self, key: &K) -> Option<AssetId<AssetManifest>> {
+        self.owners.get(key).copied()
+    }
+
+    /// Replace the entries of manifest `id` with the entries of `manifest`.
+    /// `source` is used in errors to name the manifest.
+    pub fn apply(
+        &mut self,
+        id: AssetId<AssetManifest>,
+        manifest: &AssetManifest,
+        source: &str,
+        paths: &mut AssetPathMap<K>,
+        groups: &mut AssetGroups<K>,
+    ) -> &[MagicianError] {
+        self.remove_entries(id, paths, groups);
+        let mut applied = vec![];
+        let mut errors = vec![];
+        for (group, key, path) in manifest.iter() {
+            match K::try_from(key.clone()) {
+                Ok(k) => {
+                    if self.owners.get(&k).is_some_and(|owner| *owner != id) {
+                        warn!(
+                            "Asset key {:?} is defined by several manifests, using {}",
+                            key, source
+                        );
+                    }
+                    self.set_entry(id, group.cloned(), k.clone(), path.clone(), paths, groups);
+                    applied.push((group.cloned(), k, path.clone()));
+                }
+                Err(_) => errors.push(MagicianError::InvalidKey(key.clone(), source.to_string())),
+            }
+        }
+        self.applied.push((id, applied));
+        self.errors.insert(id, errors);
+        self.errors[&id].as_slice()
+    }
+
+    /// Use the entry of manifest `id` for `key`.
+    fn set_entry(
+        &mut self,
+        id: AssetId<AssetManifest>,
+        group: Option<String>,
+        key: K,
+        path: String,
+        paths: &mut AssetPathMap<K>,
+        groups: &mut AssetGroups<K>,
+    ) {
+        groups.remove_key(&key);
+        if let Some(group) = group {
+            groups.insert(group, key.clone());
+        }
+        paths.insert(key.clone(), path);
+        self.owners.insert(key, id);
+    }
+
+    /// Remove the entries of manifest `id`. Keys it owns fall back to the most recently applied manifest
+    /// that defines them.
+    fn remove_entries(
+        &mut self,
+        id: AssetId<AssetManifest>,
+        paths: &mut AssetPathMap<K>,
+        groups: &mut AssetGroups<K>,
+    ) {
+        let Some(index) = self.applied.iter().position(|(other, _)| *other == id) else {
+            return;
+        };
+        let (_, entries) = self.applied.remove(index);
+        for (_, key, _) in entries {
+            if self.owners.get(&key) != Some(&id) {
+                continue;
+            }
+            self.owners.remove(&key);
+            paths.remove(&key);
+            groups.remove_key(&key);
+            let fallback = self.applied.iter().rev().find_map(|(other, entries)| {
+                entries
+                    .iter()
+                    .find(|(_, k, _)| *k == key)
+                    .map(|(group, _, path)| (*other, group.clone(), path.clone()))
+            });
+            if let Some((other, group, path)) = fallback {
+                self.set_entry(other, group, key, path, paths, groups);
+            }
+        }
+    }
+}
+
+/// Applies (re)loaded manifests to `AssetPathMap<K>` and logs invalid keys.
+pub fn apply_asset_manifests<K: AssetKey>(
+    mut events: EventReader<AssetEvent<AssetManifest>>,
+    assets: Res<Assets<AssetManifest>>,
+    asset_server: Res<AssetServer>,
+    mut manifests: ResMut<AssetManifests<K>>,
+    mut paths: ResMut<AssetPathMap<K>>,
+    mut groups: ResMut<AssetGroups<K>>,
+) {
+    for event in events.read() {
+        let id = match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id,
+            _ => continue,
+        };
+        if !manifests.handles.iter().any(|h| h.id() == id) {
+            continue;
+        }
+        let Some(manifest) = assets.get(id) else {
+            continue;
+        };
+        let source = asset_server
+            .get_path(id)
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| format!("{:?}", id));
+        for e in manifests.apply(id, manifest, &source, &mut paths, &mut groups) {
+            error!("{}", e);
+        }
+    }
+}
+
+/// AssetLoader for `AssetManifest`
+#[derive(Default)]
+pub struct AssetManifestLoader {}
+
+/// Loading errors for `AssetManifestLoader`
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AssetManifestLoadingError {
+    /// An [IO](std::io) Error
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for AssetManifestLoader {
+    type Asset = AssetManifest;
+    type Settings = ();
+    type Error = AssetManifestLoadingError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        ron::de::from_bytes::<AssetManifest>(&bytes).map_err(|e| e.into())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{key, Key};
+
+    fn manifest(entries: &[(&str, &str)], group: Option<&str>) -> AssetManifest {
+        let entries = entries
+            .iter()
+            .map(|(k, p)| (k.to_string(), p.to_string()))
+            .collect::<BTreeMap<_, _>>();
+        match group {
+            Some(group) => AssetManifest {
+                groups: BTreeMap::from([(group.to_string(), entries)]),
+                ..Default::default()
+            },
+            None => AssetManifest {
+                entries,
+                ..Default::default()
+            },
+        }
+    }
+
+    struct Maps {
+        manifests: AssetManifests<Key>,
+        paths: AssetPathMap<Key>,
+        groups: AssetGroups<Key>,
+    }
+
+    impl Maps {
+        fn new() -> Self {
+            Self {
+                manifests: AssetManifests::default(),
+                paths: AssetPathMap::default(),
+                groups: AssetGroups::default(),
+            }
+        }
+
+        fn apply(&mut self, id: u128, manifest: &AssetManifest) {
+            let id = Handle::<AssetManifest>::weak_from_u128(id).id();
+            self.manifests
+                .apply(id, manifest, "test", &mut self.paths, &mut self.groups);
+        }
+
+        fn unload(&mut self, id: u128) {
+            let handle = Handle::<AssetManifest>::weak_from_u128(id);
+            self.manifests
+                .unload(&handle, &mut self.paths, &mut self.groups);
+        }
+
+        fn path(&self, name: &str) -> Option<&str> {
+            self.paths.0.get(&key(name)).map(String::as_str)
+        }
+    }
+
+    #[test]
+    fn unload_keeps_keys_of_other_manifests() {
+        let mut maps = Maps::new();
+        maps.apply(
+            1,
+            &manifest(&[("tree", "a.png"), ("rock", "rock.png")], None),
+        );
+        maps.apply(2, &manifest(&[("tree", "b.png")], Some("forest")));
+        assert_eq!(maps.path("tree"), Some("b.png"));
+        assert_eq!(
+            maps.manifests.owner(&key("tree")),
+            Some(Handle::<AssetManifest>::weak_from_u128(2).id())
+        );
+        maps.unload(1);
+        assert_eq!(maps.path("tree"), Some("b.png"));
+        assert_eq!(maps.path("rock"), None);
+        assert!(maps.groups.get("forest").unwrap().contains(&key("tree")));
+        maps.unload(2);
+        assert_eq!(maps.path("tree"), None);
+        assert!(maps.groups.get("forest").is_none());
+    }
+
+    #[test]
+    fn unloading_the_owner_falls_back() {
+        let mut maps = Maps::new();
+        maps.apply(1, &manifest(&[("tree", "a.png")], Some("forest")));
+        maps.apply(2, &manifest(&[("tree", "b.png")], None));
+        assert!(maps.groups.get("forest").is_none());
+        maps.unload(2);
+        assert_eq!(maps.path("tree"), Some("a.png"));
+        assert!(maps.groups.get("forest").unwrap().contains(&key("tree")));
+    }
+
+    #[test]
+    fn fallback_uses_the_last_applied_manifest() {
+        let mut maps = Maps::new();
+        maps.apply(1, &manifest(&[("tree", "a.png")], None));
+        maps.apply(2, &manifest(&[("tree", "b.png")], None));
+        maps.apply(3, &manifest(&[("tree", "c.png")], None));
+        // Reapplying moves a manifest to the end.
+        maps.apply(1, &manifest(&[("tree", "a.png")], None));
+        maps.unload(1);
+        assert_eq!(maps.path("tree"), Some("c.png"));
+        maps.unload(3);
+        assert_eq!(maps.path("tree"), Some("b.png"));
+        assert_eq!(
+            maps.manifests.owner(&key("tree")),
+            Some(Handle::<AssetManifest>::weak_from_u128(2).id())
+        );
+    }
+
+    #[test]
+    fn reapply_replaces_entries() {
+        let mut maps = Maps::new();
+        maps.apply(
+            1,
+            &manifest(&[("tree", "a.png"), ("rock", "rock.png")], None),
+        );
+        maps.apply(1, &manifest(&[("tree", "c.png")], None));
+        assert_eq!(maps.path("tree"), Some("c.png"));
+        assert_eq!(maps.path("rock"), None);
+    }
+}
@@ -0,0 +1,190 @@
+//! Loading progress of handle maps
+//!
+//! `LoadingProgress` counts loaded, loading and failed assets across any number of `HandleMap`s,
+//! e.g. for a loading screen: `LoadingProgress::default().with_map(&sprites, &server).with_map(&sheets, &server)`.
+//!
+//! # Example usage:
+//! ```
+//! use bevy_tarot_magician::progress::LoadingProgress;
+//! let mut progress = LoadingProgress {
+//!     loaded: 2,
+//!     loading: 1,
+//!     ..Default::default()
+//! };
+//! assert_eq!(progress.total(), 3);
+//! assert!(!progress.is_done());
+//! progress.add(LoadingProgress {
+//!     loaded: 1,
+//!     ..Default::default()
+//! });
+//! assert_eq!(progress.fraction(), 0.75);
+//! assert!(LoadingProgress::default().is_complete());
+//! ```
+
+use crate::{AssetKey, HandleMap};
+use bevy_asset::prelude::*;
+use bevy_asset::{AssetLoadError, AssetPath, LoadState, RecursiveDependencyLoadState};
+
+/// Asset of a `HandleMap` that failed to load.
+#[derive(Clone, Debug)]
+pub struct LoadingFailure {
+    /// Debug representation of the `AssetKey`
+    pub key: String,
+    /// Path of the asset
+    pub path: Option<AssetPath<'static>>,
+    /// Error of the `AssetServer`, `None` if only a dependency of the asset failed.
+    pub error: Option<AssetLoadError>,
+}
+
+/// Loaded, loading and failed assets of one or more `HandleMap`s.
+#[derive(Clone, Debug, Default)]
+pub struct LoadingProgress {
+    /// Number of assets loaded with their dependencies
+    pub loaded: usize,
+    /// Number of assets that are still loading
+    pub loading: usize,
+    /// Assets that failed to load
+    pub failed: Vec<LoadingFailure>,
+}
+
+impl LoadingProgress {
+    /// Add the progress of `handle_map`.
+    pub fn with_map<K: AssetKey, A: Asset>(
+        mut self,
+        handle_map: &HandleMap<K, A>,
+        asset_server: &AssetServer,
+    ) -> Self {
+        self.add(handle_map.progress(asset_server));
+        self
+    }
+
+    /// Add the counts and failures of `other`.
+    pub fn add(&mut self, other: LoadingProgress) {
+        self.loaded += other.loaded;
+        self.loading += other.loading;
+        self.failed.extend(other.failed);
+    }
+
+    /// Number of all assets.
+    pub fn total(&self) -> usize {
+        self.loaded + self.loading + self.failed.len()
+    }
+
+    /// Share of assets that are done loading (loaded or failed), 1 if there are none.
+    pub fn fraction(&self) -> f32 {
+        match self.total() {
+            0 => 1.,
+            total => (total - self.loading) as f32 / total as f32,
+        }
+    }
+
+    /// Check if no asset is loading anymore.
+    pub fn is_done(&self) -> bool {
+        self.loading == 0
+    }
+
+    /// Check if all assets are loaded without failures.
+    pub fn is_complete(&self) -> bool {
+        self.is_done() && self.failed.is_empty()
+    }
+}
+
+impl<K: AssetKey, A: Asset> HandleMap<K, A> {
+    /// Loading progress of all assets in the map.
+    /// Handles not loaded through the `AssetServer` (e.g. added to `Assets` directly) count as loaded.
+    pub fn progress(&self, asset_server: &AssetServer) -> LoadingProgress {
+        let mut progress = LoadingProgress::default();
+        for (key, index) in self.map.iter() {
+            let Some((_, handle)) = self.handles.get(*index) else {
+                continue;
+            };
+            let failure = |error| LoadingFailure {
+                key: format!("{:?}", key),
+                path: handle.path().map(|p| p.clone_owned()),
+                error,
+            };
+            match asset_server.get_load_states(handle) {
+                None | Some((_, _, RecursiveDependencyLoadState::Loaded)) => progress.loaded += 1,
+                Some((LoadState::Failed(e), _, _)) => progress.failed.push(failure(Some(*e))),
+                Some((_, _, RecursiveDependencyLoadState::Failed)) => {
+                    progress.failed.push(failure(None))
+                }
+                Some(_) => progress.loading += 1,
+            }
+        }
+        progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{app, key, update_until, Key};
+    use crate::{load_asset, AssetPathMap, SpriteSheet};
+    use bevy_asset::io::AssetReaderError;
+
+    #[test]
+    fn failed_assets_are_reported() {
+        let mut app = app();
+        let server = app.world().resource::<AssetServer>().clone();
+        let mut paths = AssetPathMap::default();
+        paths.insert(key("broken"), "broken.png".to_string());
+        paths.insert(key("missing"), "missing.png".to_string());
+        let mut handle_map = HandleMap::<Key, SpriteSheet>::default();
+        load_asset(key("broken"), &paths, &mut handle_map, &server).unwrap();
+        load_asset(key("missing"), &paths, &mut handle_map, &server).unwrap();
+        update_until(&mut app, |_| handle_map.progress(&server).is_done());
+        let progress = handle_map.progress(&server);
+        assert_eq!(progress.total(), 2);
+        assert_eq!(progress.failed.len(), 2);
+        let error = |name| {
+            progress
+                .failed
+                .iter()
+                .find(|f| f.key == format!("{:?}", key(name)))
+                .and_then(|f| f.error.as_ref())
+        };
+        assert!(matches!(
+            error("broken"),
+            Some(AssetLoadError::AssetLoaderError(_))
+        ));
+        assert!(matches!(
+            error("missing"),
+            Some(AssetLoadError::AssetReaderError(
+                AssetReaderError::NotFound(_)
+            ))
+        ));
+        assert!(progress.is_done() && !progress.is_complete());
+        assert_eq!(progress.fraction(), 1.);
+    }
+
+    #[test]
+    fn mixed_states_are_counted() {
+        let mut app = app();
+        let server = app.world().resource::<AssetServer>().clone();
+        let mut paths = AssetPathMap::default();
+        paths.insert(key("hero"), "hero.png".to_string());
+        paths.insert(key("broken"), "broken.png".to_string());
+        paths.insert(key("sheet"), "sheet.png".to_string());
+        let mut handle_map = HandleMap::<Key, SpriteSheet>::default();
+        load_asset(key("hero"), &paths, &mut handle_map, &server).unwrap();
+        load_asset(key("broken"), &paths, &mut handle_map, &server).unwrap();
+        update_until(&mut app, |_| handle_map.progress(&server).is_done());
+        // Load results are only applied on the next update.
+        load_asset(key("sheet"), &paths, &mut handle_map, &server).unwrap();
+        let progress = handle_map.progress(&server);
+        assert_eq!(
+            (progress.loaded, progress.loading, progress.failed.len()),
+            (1, 1, 1)
+        );
+        assert_eq!(progress.failed[0].key, format!("{:?}", key("broken")));
+        assert_eq!(progress.fraction(), 2. / 3.);
+        assert!(!progress.is_done());
+        update_until(&mut app, |_| handle_map.progress(&server).is_done());
+        let progress = handle_map.progress(&server);
+        assert_eq!(
+            (progress.loaded, progress.loading, progress.failed.len()),
+            (2, 0, 1)
+        );
+    }
+}
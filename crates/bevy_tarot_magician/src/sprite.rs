@@ -1,4 +1,15 @@
 //! Sprite management
+//!
+//! # Example usage:
+//! ```
+//! use bevy_tarot_magician::sprite::SpriteSheetGrid;
+//! // 3 columns and 2 rows of 10x10 pixels with 2 pixels between the cells and a 1 pixel border.
+//! let grid = SpriteSheetGrid::new(2, 3).with_padding((2, 2)).with_margin((1, 1));
+//! assert_eq!(grid.cell_size((36, 24)), (10, 10));
+//! assert_eq!(grid.cell_min((10, 10), 1, 2), (25, 13));
+//! assert_eq!(grid.leftover((36, 24)), (0, 0));
+//! assert_eq!(grid.with_count(5).len(), 5);
+//! ```
 
 use crate::{load_asset, MagicianError, AssetPathMap, TarotAsset, HandleMap, SimpleToString, AssetKey};
 use std::default::Default;
@@ -25,7 +36,11 @@ pub type TextureAtlasLayoutHandleMap<K> = HandleMap<K, TextureAtlasLayout>;
 /// Alias for `AssetPathMap` - TODO: Remove
 pub type SpritePathMap<K> = AssetPathMap<K>;
 
-impl TarotAsset for Image {}
+impl TarotAsset for Image {
+    fn memory_size(&self) -> usize {
+        self.data.len()
+    }
+}
 
 /// Trigger event to add a sprite to an existing entity.
 #[derive(Event)]
@@ -36,9 +51,104 @@ pub struct AddSpriteToEntity<K : SpriteAssetKey> {
     pub key: K,
     /// Index of the sprite if its part of a sprite sheet.
     pub index: Option<usize>,
+    /// Name of the sprite in the sprite sheet, takes precedence over `index`.
+    /// The sprite sheet has to be loaded to look up the name.
+    pub name: Option<String>,
+}
+
+impl<K : SpriteAssetKey> AddSpriteToEntity<K> {
+    /// Simpel Constructor
+    pub fn new(entity: Entity, key: K) -> Self {
+        Self {
+            entity,
+            key,
+            index: None,
+            name: None,
+        }
+    }
+
+    /// Use the sprite at `index` of the sprite sheet.
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Use the sprite named `name` in the sprite sheet.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+/// Sprite sheet of an entity added by `AddSpriteToEntity`, keeps `Sprite::anchor` on the pivot
+/// of the current `TextureAtlas::index` (see `update_sprite_anchors`).
+#[derive(Component, Clone, Debug)]
+pub struct SpriteSheetPivots(pub Handle<SpriteSheet>);
+
+/// Updates `Sprite::anchor` from the sprite sheet pivots whenever `TextureAtlas::index` changed,
+/// e.g. by an `AnimationPlayer`. Sprites without a pivot use the default anchor.
+pub fn update_sprite_anchors(
+    sprite_sheets: Res<Assets<SpriteSheet>>,
+    mut query: Query<(&SpriteSheetPivots, &TextureAtlas, &mut Sprite), Changed<TextureAtlas>>,
+) {
+    for (pivots, atlas, mut sprite) in query.iter_mut() {
+        let Some(sheet) = sprite_sheets.get(&pivots.0) else {
+            continue;
+        };
+        let anchor = sheet.anchor(atlas.index as u32).unwrap_or_default();
+        if sprite.anchor != anchor {
+            sprite.anchor = anchor;
+        }
+    }
+}
+
+/// Applies `SpriteSheetGrid::skip_empty` once both the sprite sheet and the image of a key are loaded
+/// and updates the texture atlas layout of the key if it was already created.
+#[allow(clippy::too_many_arguments)]
+pub fn skip_empty_grid_cells<K : SpriteAssetKey>(
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut sheet_events: EventReader<AssetEvent<SpriteSheet>>,
+    images: Res<Assets<Image>>,
+    mut sprite_sheets: ResMut<Assets<SpriteSheet>>,
+    sprite_handle_map: Res<SpriteHandleMap<K>>,
+    sprite_sheet_handle_map: Res<SpriteSheetHandleMap<K>>,
+    atlas_layout_handle_map: Res<TextureAtlasLayoutHandleMap<K>>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let image_keys = image_events.read().filter_map(|event| match event {
+        AssetEvent::LoadedWithDependencies { id } => sprite_handle_map.get_key(id),
+        _ => None,
+    });
+    let sheet_keys = sheet_events.read().filter_map(|event| match event {
+        AssetEvent::LoadedWithDependencies { id } => sprite_sheet_handle_map.get_key(id),
+        _ => None,
+    });
+    let keys = image_keys.chain(sheet_keys).cloned().collect::<Vec<_>>();
+    for key in keys {
+        let Some(image) = sprite_handle_map.get(&key).and_then(|h| images.get(&h)) else {
+            continue;
+        };
+        let Some(sheet) = sprite_sheet_handle_map
+            .get(&key)
+            .and_then(|h| sprite_sheets.get_mut(&h))
+        else {
+            continue;
+        };
+        match &mut sheet.layout {
+            SpriteSheetLayout::Grid(grid) if grid.skip_empty => grid.skip_empty_cells(image),
+            _ => continue,
+        }
+        if let Some(layout) = atlas_layout_handle_map
+            .get(&key)
+            .and_then(|h| atlas_layouts.get_mut(&h))
+        {
+            *layout = (&*sheet).into();
+        }
+    }
 }
 
 /// Triggered system for adding sprites to entities.
+#[allow(clippy::too_many_arguments)]
 pub fn add_sprite_to_entity<K : SpriteAssetKey>(
     trigger: Trigger<AddSpriteToEntity<K>>,
     query: Query<&Transform>,
@@ -67,6 +177,7 @@ pub fn add_sprite_to_entity<K : SpriteAssetKey>(
 }
 
 /// Try to add sprite to entity (should rarely be called directly)
+#[allow(clippy::too_many_arguments)]
 fn try_add_sprite_to_entity<K : SpriteAssetKey>(
     event: &AddSpriteToEntity<K>,
     query: Query<&Transform>,
@@ -86,9 +197,34 @@ fn try_add_sprite_to_entity<K : SpriteAssetKey>(
     let transform = *query
         .get(entity.id())
         .map_err(|_| MagicianError::EntityNotFound(entity.id()))?;
+    let sprite_sheet = sprite_sheet_handle_map
+        .get(&event.key)
+        .and_then(|h| sprite_sheet_data.get(&h));
+    let index = match (&event.name, sprite_sheet) {
+        (Some(name), Some(sheet)) => Some(sheet.index_of(name).ok_or(
+            MagicianError::SpriteNotFound(name.clone(), event.key.sstr()),
+        )? as usize),
+        (Some(_), None) => {
+            return Err(match sprite_sheet_handle_map.get(&event.key) {
+                Some(_) => MagicianError::NotLoaded(
+                    SpriteSheet::short_type_path().to_string(),
+                    event.key.sstr(),
+                ),
+                None => MagicianError::AssetNotFound(event.key.sstr()),
+            })
+        }
+        (None, _) => event.index,
+    };
+    let anchor = sprite_sheet
+        .and_then(|sheet| sheet.anchor(index.unwrap_or_default() as u32))
+        .unwrap_or_default();
     let sprite_bundle = SpriteBundle {
         transform,
         texture: sprite,
+        sprite: Sprite {
+            anchor,
+            ..Default::default()
+        },
         ..Default::default()
     };
     entity.insert(sprite_bundle);
@@ -102,12 +238,15 @@ fn try_add_sprite_to_entity<K : SpriteAssetKey>(
         Ok(layout) => {
             let atlas = TextureAtlas {
                 layout,
-                index: event.index.unwrap_or_default(),
+                index: index.unwrap_or_default(),
             };
             entity.insert(atlas);
+            if let Some(sheet) = sprite_sheet_handle_map.get(&event.key) {
+                entity.insert(SpriteSheetPivots(sheet));
+            }
         }
         Err(_) => {
-            if let Some(index) = event.index {
+            if let Some(index) = index {
                 warn!(
                     "Added Sprite to Entity {} with index {} but no sprite sheet was found for {}",
                     entity.id(),
@@ -161,11 +300,14 @@ mod sprite_sheet {
     //! Sprite sheet reprensetation.
     use serde::{Deserialize, Serialize};
     use std::cell::RefCell;
+    use std::collections::{BTreeMap, BTreeSet};
     use bevy_asset::{AssetLoader, LoadContext, ron, AsyncReadExt};
     use bevy_asset::io::Reader;
     use bevy_math::URect;
     use bevy_reflect::TypePath;
-    use bevy_sprite::TextureAtlasLayout;
+    use bevy_render::texture::Image;
+    use bevy_math::Vec2;
+    use bevy_sprite::{Anchor, TextureAtlasLayout};
     use thiserror::Error;
     use crate::*;
 
@@ -189,6 +331,9 @@ mod sprite_sheet {
         #[serde(skip)]
         /// Cache sprite size for grid layout.
         pub(crate) grid_sprite_size: RefCell<Option<(u32, u32)>>,
+        /// Names, pivots and tags of sprites by index.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub(crate) sprites: BTreeMap<u32, SpriteMeta>,
     }
 
     impl From<&SpriteSheet> for TextureAtlasLayout {
@@ -220,15 +365,91 @@ mod sprite_sheet {
             }
         }
 
+        /// Sheet has no sprites.
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Metadata of the sprite at `index`.
+        pub fn meta(&self, index: u32) -> Option<&SpriteMeta> {
+            self.sprites.get(&index)
+        }
+
+        /// Set the metadata of the sprite at `index`.
+        pub fn set_meta(&mut self, index: u32, meta: SpriteMeta) {
+            self.sprites.insert(index, meta);
+        }
+
+        /// Index of the sprite named `name`, the lowest one if several sprites share the name.
+        pub fn index_of(&self, name: &str) -> Option<u32> {
+            self.sprites
+                .iter()
+                .find(|(_, m)| m.name.as_deref() == Some(name))
+                .map(|(i, _)| *i)
+        }
+
+        /// Names of all named sprites with their index.
+        pub fn names(&self) -> impl Iterator<Item = (u32, &String)> {
+            self.sprites
+                .iter()
+                .filter_map(|(i, m)| m.name.as_ref().map(|n| (*i, n)))
+        }
+
+        /// Indices of all sprites tagged with `tag`.
+        pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = u32> + 'a {
+            self.sprites
+                .iter()
+                .filter(move |(_, m)| m.tags.iter().any(|t| t == tag))
+                .map(|(i, _)| *i)
+        }
+
+        /// Names used by more than one sprite.
+        pub fn duplicate_names(&self) -> BTreeSet<&str> {
+            let mut names = BTreeSet::new();
+            self.sprites
+                .values()
+                .filter_map(|m| m.name.as_deref())
+                .filter(|name| !names.insert(*name))
+                .collect()
+        }
+
+        /// Log a warning for every name used by more than one sprite, `index_of` only finds the first one.
+        pub(crate) fn warn_duplicate_names(&self, path: &std::path::Path) {
+            for name in self.duplicate_names() {
+                warn!(
+                    "Sprite name {:?} is used more than once in {:?}, only the first sprite can be found by name",
+                    name, path
+                );
+            }
+        }
+
+        /// All tags used in the sheet.
+        pub fn tags(&self) -> BTreeSet<&String> {
+            self.sprites.values().flat_map(|m| m.tags.iter()).collect()
+        }
+
+        /// Anchor of the sprite at `index` from its pivot.
+        pub fn anchor(&self, index: u32) -> Option<Anchor> {
+            let pivot = self.meta(index)?.pivot?;
+            let sprite = self.get(index)?;
+            let size: Vec2 = sprite.into();
+            if size.x <= 0. || size.y <= 0. {
+                return None;
+            }
+            Some(Anchor::Custom(Vec2::new(
+                pivot.0 / size.x - 0.5,
+                0.5 - pivot.1 / size.y,
+            )))
+        }
+
         /// Get Sprite data for index (grid is enumerated as row1, row2 ...)
         pub fn get(&self, index: u32) -> Option<SpriteData> {
             match &self.layout {
                 SpriteSheetLayout::Grid(g) => {
-                    // TODO: Check this again
-                    let row = index / g.cols;
-                    if row >= g.rows {
+                    if index as usize >= g.len() {
                         return None;
                     }
+                    let row = index / g.cols;
                     let col = index % g.cols;
                     // TODO: This seems overkill
                     let sprite_size = {
@@ -236,14 +457,17 @@ mod sprite_sheet {
                         if let Some(s) = *spr_size {
                             s
                         } else {
-                            let sprite_size = (self.size.0 / g.cols, self.size.1 / g.rows);
+                            let sprite_size = g.cell_size(self.size);
                             *spr_size = Some(sprite_size);
                             sprite_size
                         }
                     };
-                    let x = sprite_size.0 * col;
-                    let y = sprite_size.1 * row;
-                    SpriteData::new((x, y), (x + sprite_size.0, y + sprite_size.1)).into()
+                    let (x, y) = g.cell_min(sprite_size, row, col);
+                    SpriteData::new(
+                        (x, y),
+                        (x.saturating_add(sprite_size.0), y.saturating_add(sprite_size.1)),
+                    )
+                    .into()
                 }
                 SpriteSheetLayout::List(list) => list.get(index as usize).copied(),
             }
@@ -256,19 +480,172 @@ mod sprite_sheet {
         }
     }
 
-    /// Grid size for a sprite sheet
+    /// Grid layout of a sprite sheet.
+    /// Only `rows` and `cols` are required in RON, the cell size is then derived from the sheet size.
     #[derive(Asset, TypePath, Debug, Serialize, Deserialize, Clone)]
     pub struct SpriteSheetGrid {
         /// rows (should be larger than 0)
-        rows: u32,
+        pub rows: u32,
         /// columns (should be larger than 0)
-        cols: u32,
+        pub cols: u32,
+        /// Size of a cell, derived from the sheet size if not set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub cell_size: Option<(u32, u32)>,
+        /// Space between two cells.
+        #[serde(default, skip_serializing_if = "is_zero")]
+        pub padding: (u32, u32),
+        /// Border around the grid on all sides.
+        #[serde(default, skip_serializing_if = "is_zero")]
+        pub margin: (u32, u32),
+        /// Additional offset of the grid from the top left corner.
+        #[serde(default, skip_serializing_if = "is_zero")]
+        pub offset: (u32, u32),
+        /// Number of used cells, trailing cells after them are skipped.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub count: Option<u32>,
+        /// Set `count` from the image once it is loaded, so trailing empty cells are skipped.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        pub skip_empty: bool,
+    }
+
+    fn is_zero(value: &(u32, u32)) -> bool {
+        *value == (0, 0)
     }
 
     impl SpriteSheetGrid {
+        /// Simpel Constructor
+        pub fn new(rows: u32, cols: u32) -> Self {
+            Self {
+                rows,
+                cols,
+                cell_size: None,
+                padding: (0, 0),
+                margin: (0, 0),
+                offset: (0, 0),
+                count: None,
+                skip_empty: false,
+            }
+        }
+
+        /// Set the size of a cell.
+        pub fn with_cell_size(mut self, cell_size: (u32, u32)) -> Self {
+            self.cell_size = Some(cell_size);
+            self
+        }
+
+        /// Set the space between two cells.
+        pub fn with_padding(mut self, padding: (u32, u32)) -> Self {
+            self.padding = padding;
+            self
+        }
+
+        /// Set the border around the grid.
+        pub fn with_margin(mut self, margin: (u32, u32)) -> Self {
+            self.margin = margin;
+            self
+        }
+
+        /// Set the offset of the grid from the top left corner.
+        pub fn with_offset(mut self, offset: (u32, u32)) -> Self {
+            self.offset = offset;
+            self
+        }
+
+        /// Set the number of used cells.
+        pub fn with_count(mut self, count: u32) -> Self {
+            self.count = Some(count);
+            self
+        }
+
+        /// Skip trailing empty cells once the image is loaded.
+        pub fn with_skip_empty(mut self) -> Self {
+            self.skip_empty = true;
+            self
+        }
+
         /// Calculate sprite count
         pub fn len(&self) -> usize {
-            (self.rows * self.cols) as usize
+            let cells = self.cells();
+            self.count.map_or(cells, |c| c.min(cells)) as usize
+        }
+
+        /// Number of cells (rows * cols), saturating at `u32::MAX`.
+        fn cells(&self) -> u32 {
+            self.rows.saturating_mul(self.cols)
+        }
+
+        /// Grid has no cells.
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Size of a cell on a sheet of `sheet_size`.
+        pub fn cell_size(&self, sheet_size: (u32, u32)) -> (u32, u32) {
+            self.cell_size.unwrap_or_else(|| {
+                let cell = |size: u32, cells: u32, padding: u32, margin: u32, offset: u32| {
+                    let spacing = 2 * margin as u64
+                        + offset as u64
+                        + cells.saturating_sub(1) as u64 * padding as u64;
+                    // At most `size`, so it fits into u32.
+                    ((size as u64).saturating_sub(spacing) / cells.max(1) as u64) as u32
+                };
+                (
+                    cell(sheet_size.0, self.cols, self.padding.0, self.margin.0, self.offset.0),
+                    cell(sheet_size.1, self.rows, self.padding.1, self.margin.1, self.offset.1),
+                )
+            })
+        }
+
+        /// Top left corner of the cell at `row` and `col`, saturating at `u32::MAX`.
+        pub fn cell_min(&self, cell_size: (u32, u32), row: u32, col: u32) -> (u32, u32) {
+            let min = |margin: u32, offset: u32, index: u32, size: u32, padding: u32| {
+                let step = (index as u64).saturating_mul(size as u64 + padding as u64);
+                let min = step.saturating_add(margin as u64 + offset as u64);
+                u32::try_from(min).unwrap_or(u32::MAX)
+            };
+            (
+                min(self.margin.0, self.offset.0, col, cell_size.0, self.padding.0),
+                min(self.margin.1, self.offset.1, row, cell_size.1, self.padding.1),
+            )
+        }
+
+        /// Pixels of a sheet of `sheet_size` that are not covered by the grid (width, height).
+        pub fn leftover(&self, sheet_size: (u32, u32)) -> (i64, i64) {
+            let cell_size = self.cell_size(sheet_size);
+            // Computed in i128, which can not overflow with u32 inputs.
+            let leftover = |sheet: u32, size: u32, cells: u32, padding: u32, margin: u32, offset: u32| {
+                let used = 2 * margin as i128
+                    + offset as i128
+                    + cells as i128 * size as i128
+                    + cells.saturating_sub(1) as i128 * padding as i128;
+                (sheet as i128 - used).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+            };
+            (
+                leftover(sheet_size.0, cell_size.0, self.cols, self.padding.0, self.margin.0, self.offset.0),
+                leftover(sheet_size.1, cell_size.1, self.rows, self.padding.1, self.margin.1, self.offset.1),
+            )
+        }
+
+        /// Set `count` so that trailing cells without any visible pixel in `image` are skipped.
+        /// Only images with 4 bytes per pixel and alpha last (e.g. RGBA8) can be checked.
+        pub fn skip_empty_cells(&mut self, image: &Image) {
+            let size = image.size();
+            let sheet_size = (size.x, size.y);
+            if image.data.len() as u64 != size.x as u64 * size.y as u64 * 4 {
+                warn!("Can not check empty cells of an image that is not RGBA8.");
+                return;
+            }
+            let cell_size = self.cell_size(sheet_size);
+            let visible = |index: u32| {
+                let (x, y) = self.cell_min(cell_size, index / self.cols, index % self.cols);
+                (y..y.saturating_add(cell_size.1).min(size.y)).any(|py| {
+                    (x..x.saturating_add(cell_size.0).min(size.x)).any(|px| {
+                        image.data[(py as usize * size.x as usize + px as usize) * 4 + 3] != 0
+                    })
+                })
+            };
+            let cells = self.cells();
+            self.count = Some((0..cells).rev().find(|i| visible(*i)).map_or(0, |i| i + 1));
         }
     }
 
@@ -276,7 +653,7 @@ mod sprite_sheet {
     #[derive(Asset, TypePath, Debug, Serialize, Deserialize, Clone)]
     #[serde(untagged)]
     pub enum SpriteSheetLayout {
-        /// Grid (rows, cols)
+        /// Grid (rows, cols, optional cell size, padding, margin, offset and count)
         Grid(SpriteSheetGrid),
         /// List with positions and sizes
         List(Vec<SpriteData>),
@@ -307,11 +684,29 @@ mod sprite_sheet {
             &'a self,
             reader: &'a mut Reader<'_>,
             _settings: &'a Self::Settings,
-            _load_context: &'a mut LoadContext<'_>,
+            load_context: &'a mut LoadContext<'_>,
         ) -> Result<Self::Asset, Self::Error> {
             let mut bytes = vec![];
             reader.read_to_end(&mut bytes).await?;
-            ron::de::from_bytes::<SpriteSheet>(&bytes).map_err(|e| e.into())
+            let sheet = ron::de::from_bytes::<SpriteSheet>(&bytes)?;
+            sheet.warn_duplicate_names(load_context.path());
+            if let SpriteSheetLayout::Grid(grid) = &sheet.layout {
+                let leftover = grid.leftover(sheet.size);
+                if leftover != (0, 0) {
+                    warn!(
+                        "Grid of {:?} does not fit its size {:?} ({:?} pixels left over)",
+                        load_context.path(),
+                        sheet.size,
+                        leftover
+                    );
+                }
+            }
+            Ok(sheet)
+        }
+
+        /// Picks this loader over `JsonSpriteSheetLoader` for `SpriteSheet`s in RON files.
+        fn extensions(&self) -> &[&str] {
+            &["ron"]
         }
     }
 
@@ -340,13 +735,218 @@ mod sprite_sheet {
             }
         }
 
+        /// Name, pivot and tags of a sprite.
+        #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+        pub struct SpriteMeta {
+            /// Name to address the sprite instead of its index.
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub name: Option<String>,
+            /// Pivot in pixels from the top left corner of the sprite, used as `Sprite::anchor`.
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub pivot: Option<(f32, f32)>,
+            /// Free-form tags, e.g. to group sprites in an editor.
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub tags: Vec<String>,
+        }
+
+        impl SpriteMeta {
+            /// Set the name.
+            pub fn with_name(mut self, name: impl Into<String>) -> Self {
+                self.name = Some(name.into());
+                self
+            }
+
+            /// Set the pivot in pixels from the top left corner.
+            pub fn with_pivot(mut self, pivot: (f32, f32)) -> Self {
+                self.pivot = Some(pivot);
+                self
+            }
+
+            /// Add a tag.
+            pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+                self.tags.push(tag.into());
+                self
+            }
+        }
+
         impl From<SpriteData> for Vec2 {
             fn from(value: SpriteData) -> Self {
                 Vec2::new(
-                    (value.max.0 - value.min.0) as f32,
-                    (value.max.1 - value.min.1) as f32,
+                    value.max.0.saturating_sub(value.min.0) as f32,
+                    value.max.1.saturating_sub(value.min.1) as f32,
                 )
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::prelude::*;
+    use bevy_math::Vec2;
+    use bevy_sprite::Anchor;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn sheet(sprites: BTreeMap<u32, SpriteMeta>) -> SpriteSheet {
+        SpriteSheet {
+            layout: SpriteSheetLayout::Grid(SpriteSheetGrid::new(1, 3)),
+            size: (30, 10),
+            grid_sprite_size: Default::default(),
+            sprites,
+        }
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let sheet = sheet(BTreeMap::from([
+            (0, SpriteMeta::default().with_name("idle")),
+            (1, SpriteMeta::default().with_name("run")),
+            (2, SpriteMeta::default().with_name("idle")),
+        ]));
+        assert_eq!(sheet.duplicate_names(), BTreeSet::from(["idle"]));
+        assert_eq!(sheet.index_of("idle"), Some(0));
+        assert_eq!(sheet.index_of("run"), Some(1));
+    }
+
+    #[test]
+    fn anchor_follows_index() {
+        let mut sheets = Assets::<SpriteSheet>::default();
+        let handle = sheets.add(sheet(BTreeMap::from([(
+            1,
+            SpriteMeta::default().with_pivot((0., 10.)),
+        )])));
+        let mut app = App::new();
+        app.insert_resource(sheets);
+        app.add_systems(Update, update_sprite_anchors);
+        let entity = app
+            .world_mut()
+            .spawn((
+                SpriteSheetPivots(handle),
+                TextureAtlas::default(),
+                Sprite::default(),
+            ))
+            .id();
+        let mut anchor_at = |index: usize| {
+            app.world_mut().entity_mut(entity).insert(TextureAtlas {
+                index,
+                ..Default::default()
+            });
+            app.update();
+            app.world().get::<Sprite>(entity).unwrap().anchor
+        };
+        assert_eq!(anchor_at(0), Anchor::Center);
+        assert_eq!(anchor_at(1), Anchor::Custom(Vec2::new(-0.5, -0.5)));
+        assert_eq!(anchor_at(2), Anchor::Center);
+    }
+
+    #[test]
+    fn anchor_of_empty_sprite() {
+        let sheet = SpriteSheet {
+            layout: SpriteSheetLayout::List(vec![SpriteData::new((10, 0), (0, 10))]),
+            size: (10, 10),
+            grid_sprite_size: Default::default(),
+            sprites: BTreeMap::from([(0, SpriteMeta::default().with_pivot((5., 5.)))]),
+        };
+        assert_eq!(Vec2::from(sheet.get(0).unwrap()), Vec2::new(0., 10.));
+        assert_eq!(sheet.anchor(0), None);
+    }
+
+    #[test]
+    fn grid_cells() {
+        let grid = SpriteSheetGrid::new(2, 3)
+            .with_padding((2, 4))
+            .with_margin((1, 2))
+            .with_offset((3, 0));
+        let sheet_size = (3 * 10 + 2 * 2 + 2 + 3, 2 * 8 + 4 + 2 * 2);
+        assert_eq!(grid.cell_size(sheet_size), (10, 8));
+        assert_eq!(grid.cell_min((10, 8), 0, 0), (4, 2));
+        assert_eq!(grid.cell_min((10, 8), 1, 2), (28, 14));
+        assert_eq!(grid.leftover(sheet_size), (0, 0));
+        assert_eq!(grid.leftover((sheet_size.0 + 2, sheet_size.1)), (2, 0));
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid.clone().with_count(4).len(), 4);
+        assert_eq!(grid.with_count(10).len(), 6);
+    }
+
+    #[test]
+    fn grid_with_cell_size() {
+        let grid = SpriteSheetGrid::new(2, 2).with_cell_size((16, 16));
+        assert_eq!(grid.cell_size((20, 40)), (16, 16));
+        assert_eq!(grid.leftover((20, 40)), (-12, 8));
+    }
+
+    #[test]
+    fn grid_does_not_overflow() {
+        let max = (u32::MAX, u32::MAX);
+        let grid = SpriteSheetGrid::new(u32::MAX, u32::MAX)
+            .with_padding(max)
+            .with_margin(max)
+            .with_offset(max);
+        assert_eq!(grid.cell_size((64, 64)), (0, 0));
+        assert_eq!(grid.cell_min(max, u32::MAX, u32::MAX), max);
+        assert_eq!(grid.leftover((64, 64)), (i64::MIN, i64::MIN));
+        assert_eq!(grid.len(), u32::MAX as usize);
+        let grid = SpriteSheetGrid::new(0, 0);
+        assert_eq!(grid.cell_size((64, 64)), (64, 64));
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    fn skip_empty_cells_once_loaded() {
+        use crate::tests::{key, Key};
+        use bevy_render::render_asset::RenderAssetUsages;
+        use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: 4,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        // Only the second of four cells has a visible pixel.
+        image.data[4 + 3] = 255;
+        let mut images = Assets::<Image>::default();
+        let image = images.add(image);
+        let mut sheets = Assets::<SpriteSheet>::default();
+        let sheet = sheets.add(SpriteSheet {
+            layout: SpriteSheetLayout::Grid(SpriteSheetGrid::new(1, 4).with_skip_empty()),
+            size: (4, 1),
+            grid_sprite_size: Default::default(),
+            sprites: BTreeMap::new(),
+        });
+        let mut layouts = Assets::<TextureAtlasLayout>::default();
+        let layout = layouts.add(TextureAtlasLayout::from(sheets.get(&sheet).unwrap()));
+        let mut sprite_handle_map = SpriteHandleMap::<Key>::default();
+        sprite_handle_map.insert(key("hero"), image.clone());
+        let mut sprite_sheet_handle_map = SpriteSheetHandleMap::<Key>::default();
+        sprite_sheet_handle_map.insert(key("hero"), sheet.clone());
+        let mut atlas_layout_handle_map = TextureAtlasLayoutHandleMap::<Key>::default();
+        atlas_layout_handle_map.insert(key("hero"), layout.clone());
+        let mut app = App::new();
+        app.insert_resource(images)
+            .insert_resource(sheets)
+            .insert_resource(layouts)
+            .insert_resource(sprite_handle_map)
+            .insert_resource(sprite_sheet_handle_map)
+            .insert_resource(atlas_layout_handle_map)
+            .add_event::<AssetEvent<Image>>()
+            .add_event::<AssetEvent<SpriteSheet>>()
+            .add_systems(Update, skip_empty_grid_cells::<Key>);
+        let layout_len = |app: &App| {
+            let layouts = app.world().resource::<Assets<TextureAtlasLayout>>();
+            layouts.get(&layout).unwrap().len()
+        };
+        assert_eq!(layout_len(&app), 4);
+        app.world_mut()
+            .send_event(AssetEvent::<Image>::LoadedWithDependencies { id: image.id() });
+        app.update();
+        let sheets = app.world().resource::<Assets<SpriteSheet>>();
+        assert_eq!(sheets.get(&sheet).unwrap().len(), 2);
+        assert_eq!(layout_len(&app), 2);
+    }
+}
@@ -3,26 +3,34 @@ use bevy_app::{App, FixedUpdate, Update};
 use bevy_core_pipeline::prelude::Camera2d;
 use bevy_ecs::prelude::*;
 use bevy_state::prelude::*;
-use bevy_tarot_chariot::prelude::{KeyCode, MouseButton};
-use bevy_tarot_chariot::{ButtonInput, ButtonMapping, InputAction, MappedButtons};
+use bevy_tarot_chariot::{trigger_action_events, ActionState, InputAction, VirtualDPad};
 use bevy_transform::prelude::*;
 use bevy_window::prelude::*;
 use serde::Serialize;
 
 pub fn plugin<S: States + Copy>(app: &mut App, state: S) {
-    app.insert_resource(EditorAction::default_mapping());
-    app.add_systems(Update, handle_input.run_if(in_state(state)));
+    app.add_plugins(bevy_tarot_chariot::plugin::<EditorAction>);
+    app.add_systems(
+        Update,
+        trigger_action_events::<EditorAction>.run_if(in_state(state)),
+    );
     app.add_systems(FixedUpdate, editor_camera_control.run_if(in_state(state)));
 }
 
 /// TODO: DO SOMETHING
 const EDGE_DIST: f32 = 50.;
 
+const PAN: VirtualDPad<EditorAction> = VirtualDPad::new(
+    EditorAction::PanUp,
+    EditorAction::PanDown,
+    EditorAction::PanLeft,
+    EditorAction::PanRight,
+);
+
 pub fn editor_camera_control(
     mut camera: Query<(&Camera2d, &mut Transform)>,
     q_windows: Query<(&Window), With<PrimaryWindow>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    editor_input_mapping: Res<ButtonMapping<EditorAction>>,
+    actions: Res<ActionState<EditorAction>>,
 ) {
     if camera.get_single().is_err() || q_windows.get_single().is_err() {
         return;
@@ -33,67 +41,33 @@ pub fn editor_camera_control(
         let max_edge_dist = EDGE_DIST / 1920. * width;
         let mut move_x = get_pan_speed_signed(pos.x, width, max_edge_dist) / 10.;
         let mut move_y = -get_pan_speed_signed(pos.y, height, max_edge_dist) / 10.;
-
-        update_move_axis(
-            &mut move_x,
-            EditorAction::PanRight,
-            EditorAction::PanLeft,
-            &editor_input_mapping,
-            &keyboard_input,
-        );
-        update_move_axis(
-            &mut move_y,
-            EditorAction::PanUp,
-            EditorAction::PanDown,
-            &editor_input_mapping,
-            &keyboard_input,
-        );
+        let pan = PAN.value(&actions) * 10.;
+        if move_x == 0. {
+            move_x = pan.x;
+        }
+        if move_y == 0. {
+            move_y = pan.y;
+        }
 
         transform.translation.x += move_x;
         transform.translation.y += move_y;
     }
 }
 
-fn handle_input(
-    mut commands: Commands,
-    kb: Res<ButtonInput<KeyCode>>,
-    ms: Res<ButtonInput<MouseButton>>,
-    mapping: Res<ButtonMapping<EditorAction>>,
-) {
-    if kb.pressed(KeyCode::ControlLeft) && kb.just_pressed(KeyCode::KeyS) {
-        commands.trigger(SaveLevel {});
-    }
-
-    if mapping.just_pressed(&EditorAction::Deselect, Some(&kb), Some(&ms), None) {
-        commands.trigger(EditorDeselect {});
-    }
-
-    if mapping.just_pressed(&EditorAction::Place, Some(&kb), Some(&ms), None) {
-        commands.trigger(EditorPlace {});
-    }
-}
-
-#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug, Serialize, Event)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug, Serialize, Event, InputAction)]
 pub enum EditorAction {
+    #[bind(KeyW)]
     PanUp,
+    #[bind(KeyS)]
     PanDown,
+    #[bind(KeyA)]
     PanLeft,
+    #[bind(KeyD)]
     PanRight,
+    #[bind(Escape)]
     Deselect,
+    #[bind(MouseButton::Left)]
     Place,
-}
-
-impl InputAction for EditorAction {
-    fn default_mapping() -> ButtonMapping<Self> {
-        use EditorAction::*;
-        use KeyCode::*;
-        let mut mapping = ButtonMapping::<Self>::default();
-        mapping.insert_mapping(MappedButtons::new_single(PanUp, KeyW.into()));
-        mapping.insert_mapping(MappedButtons::new_single(PanDown, KeyS.into()));
-        mapping.insert_mapping(MappedButtons::new_single(PanLeft, KeyA.into()));
-        mapping.insert_mapping(MappedButtons::new_single(PanRight, KeyD.into()));
-        mapping.insert_mapping(MappedButtons::new_single(Deselect, Escape.into()));
-        mapping.insert_mapping(MappedButtons::new_single(Place, MouseButton::Left.into()));
-        mapping
-    }
+    #[bind(ControlLeft + KeyS)]
+    Save,
 }
@@ -12,8 +12,7 @@ use bevy_ecs::prelude::*;
 use bevy_egui::EguiUserTextures;
 use bevy_math::prelude::*;
 use bevy_state::state::{OnExit, States};
-use bevy_tarot_chariot::keyboard::KeyCode;
-use bevy_tarot_chariot::{ButtonInput, ButtonMapping};
+use bevy_tarot_chariot::ActionPressed;
 use bevy_tarot_hermit::unwrap_option_continue;
 use bevy_tarot_hermit::*;
 use bevy_tarot_world::level::{LevelBuilder, LevelElement};
@@ -98,14 +97,14 @@ pub struct SetSelectedEditorObject<K: AssetKey> {
     pub atlas: TextureAtlas,
 }
 
-#[derive(Event)]
-pub struct EditorDeselect {}
-
 pub fn deselect(
-    _trigger: Trigger<EditorDeselect>,
+    trigger: Trigger<ActionPressed<EditorAction>>,
     mut commands: Commands,
     obj: Query<Entity, With<SelectedEditorObjectPreview>>,
 ) {
+    if trigger.event().action != EditorAction::Deselect {
+        return;
+    }
     let e = get_single!(obj);
     unwrap_option!(commands.get_entity(e)).despawn();
 }
@@ -288,33 +287,11 @@ fn get_pan_speed_signed(pos: f32, size: f32, max_edge_dist: f32) -> f32 {
     }
 }
 
-/// TODO: Shouldnt this be in input?
-fn update_move_axis(
-    move_axis: &mut f32,
-    positive_action: EditorAction,
-    negative_action: EditorAction,
-    mapping: &ButtonMapping<EditorAction>,
-    input: &ButtonInput<KeyCode>,
-) {
-    if *move_axis == 0. {
-        if mapping.pressed(&positive_action, Some(input), None, None) {
-            *move_axis += 10.;
-        }
-
-        if mapping.pressed(&negative_action, Some(input), None, None) {
-            *move_axis -= 10.;
-        }
-    }
-}
-
-#[derive(Event)]
-pub struct EditorPlace {}
-
 #[derive(Component)]
 pub struct PlacedObject {}
 
 pub fn place_selection<K: AssetKey + Component>(
-    _trigger: Trigger<EditorPlace>,
+    trigger: Trigger<ActionPressed<EditorAction>>,
     mut commands: Commands,
     mut selection: Query<
         (
@@ -328,6 +305,9 @@ pub fn place_selection<K: AssetKey + Component>(
         With<SelectedEditorObjectPreview>,
     >,
 ) {
+    if trigger.event().action != EditorAction::Place {
+        return;
+    }
     let (entity, mut transform, colliding, mut sprite, atlas, key) = get_single_mut!(selection);
     let atlas = atlas.clone();
     if colliding.map(|c| !c.0.is_empty()).unwrap_or_default() {
@@ -417,13 +397,13 @@ pub fn editor_add_sprite<K: AssetKey>(
     }
 }
 
-#[derive(Event)]
-pub struct SaveLevel {}
-
 pub fn save_level<K: AssetKey + Component, S: LevelElement, D: LevelElement>(
-    _trigger: Trigger<SaveLevel>,
+    trigger: Trigger<ActionPressed<EditorAction>>,
     query: StaticElementQuery<K>,
 ) {
+    if trigger.event().action != EditorAction::Save {
+        return;
+    }
     let builder = generate_level_builder::<K, S, D>(&query);
     let r = ron::ser::to_string_pretty(&builder, Default::default()).unwrap();
     std::fs::write("test.ron", r).unwrap();
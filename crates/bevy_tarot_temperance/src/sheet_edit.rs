@@ -45,7 +45,7 @@ fn update_sprite_sheet(trigger: Trigger<UpdateSpriteSheet>, mut sheet: ResMut<Ed
         UpdateSpriteSheet::ToGrid(rows, cols) => {
             match layout {
                 SpriteSheetLayout::Grid(_) => { return; }
-                SpriteSheetLayout::List(_) => { *layout = SpriteSheetLayout::Grid(SpriteSheetGrid { rows: *rows, cols: *cols  })}
+                SpriteSheetLayout::List(_) => { *layout = SpriteSheetLayout::Grid(SpriteSheetGrid::new(*rows, *cols))}
             }
         }
         UpdateSpriteSheet::ToList => {
@@ -79,12 +79,13 @@ fn draw_sprite_sheet(sprite_sheet: Res<EditingSpriteSheet>, mut gizmos: Gizmos)
     let sheet_size = tuple_u32_to_vec2(size);
     match &sheet.layout {
         SpriteSheetLayout::Grid(grid) => {
-            let sprite_size = tuple_u32_to_vec2((size.0 / grid.cols, size.1 / grid.rows));
-            for i in 0..grid.cols {
-                for j in 0..grid.rows {
-                    let pos = Vec2::new(i as f32 * sprite_size.x, j as f32 * sprite_size.y) - (sheet_size - sprite_size)/2. ;
-                    gizmos.rect_2d(pos, 0., sprite_size, bevy_color::Color::WHITE)
-                }
+            let cell_size = grid.cell_size(size);
+            let sprite_size = tuple_u32_to_vec2(cell_size);
+            // Only the used cells, trailing cells after `count` are skipped.
+            for index in 0..grid.len() as u32 {
+                let (row, col) = (index / grid.cols, index % grid.cols);
+                let pos = tuple_u32_to_vec2(grid.cell_min(cell_size, row, col)) - (sheet_size - sprite_size)/2. ;
+                gizmos.rect_2d(pos, 0., sprite_size, bevy_color::Color::WHITE)
             }
         }
         SpriteSheetLayout::List(l) => {
@@ -122,9 +123,10 @@ fn init_loaded_sprite(mut commands: Commands, mut asset_events: EventReader<Asse
                 let loaded_image = unwrap_option_continue!(image_assets.get(*id));
                 let size = loaded_image.size();
                 let new_sprite_sheet = SpriteSheet {
-                    layout: SpriteSheetLayout::Grid(SpriteSheetGrid { rows: 1, cols: 1 }),
+                    layout: SpriteSheetLayout::Grid(SpriteSheetGrid::new(1, 1)),
                     size: (size.x, size.y),
                     grid_sprite_size: Default::default(),
+                    sprites: Default::default(),
                 };
                 sprite_sheet.sheet = Some(new_sprite_sheet);
                 let e = commands.spawn( SpriteBundle {
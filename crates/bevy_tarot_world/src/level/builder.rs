@@ -97,6 +97,10 @@ pub struct StaticLevelElementBuilder<L> {
     #[serde(skip_serializing_if = "is_default")]
     /// Sprite index (for Texture atlas)
     pub sprite_index: Option<usize>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Sprite name in the sprite sheet, takes precedence over `sprite_index`
+    pub sprite_name: Option<String>,
 }
 
 impl<L> StaticLevelElementBuilder<L> {
@@ -110,6 +114,7 @@ impl<L> StaticLevelElementBuilder<L> {
             collider: None,
             sprite: key.into(),
             sprite_index: None,
+            sprite_name: None,
         }
     }
 
@@ -119,6 +124,12 @@ impl<L> StaticLevelElementBuilder<L> {
         self
     }
 
+    /// Add a sprite name from the sprite sheet (TextureAtlas)
+    pub fn with_sprite_name(mut self, name: impl Into<String>) -> Self {
+        self.sprite_name = Some(name.into());
+        self
+    }
+
     /// Set Transform
     /// TODO: Rotation
     pub fn set_transform(&mut self, transform: &Transform) {
@@ -169,6 +180,7 @@ impl<L : WorldLayer> StaticLevelElementBuilder<L> {
             entity: id,
             key,
             index: self.sprite_index,
+            name: self.sprite_name.clone(),
         });
         Ok(id)
     }
@@ -8,17 +8,20 @@ use std::fmt::Formatter;
 use bevy_ecs::prelude::*;
 use bevy_math::{Rect, Vec2};
 use bevy_tarot_hermit::math::dist_to_rect;
+use bevy_tarot_magician::animation::{load_animation_set, AnimationSetHandleMap};
 use bevy_tarot_magician::sprite::{load_sprite, load_sprite_sheet, SpriteHandleMap, SpritePathMap, SpriteSheetHandleMap};
 use bevy_tarot_magician::{AssetKey, AssetServer, SpriteAssetKey};
 use ron::de::SpannedError;
 
 /// Start loading all assets in a level.
+/// Sprite sheets and animation sets are optional, their handles are dropped once their files are known to be missing.
 pub fn load_level_assets<K : SpriteAssetKey, L : WorldLayer>(
     level: &LevelBuilder<L>,
     asset_server: &AssetServer,
     sprite_paths: &SpritePathMap<K>,
     sprite_handle_map: &mut SpriteHandleMap<K>,
     sprite_sheet_handle_map: &mut SpriteSheetHandleMap<K>,
+    animation_set_handle_map: &mut AnimationSetHandleMap<K>,
 ) {
     for sprite in level.sprite_keys::<K>() {
         let _ = load_sprite(
@@ -33,6 +36,12 @@ pub fn load_level_assets<K : SpriteAssetKey, L : WorldLayer>(
             sprite_sheet_handle_map,
             asset_server,
         );
+        let _ = load_animation_set(
+            sprite.clone(),
+            sprite_paths,
+            animation_set_handle_map,
+            asset_server,
+        );
     }
 }
 